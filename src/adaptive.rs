@@ -0,0 +1,427 @@
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Deref};
+
+use num_traits::Float;
+#[cfg(feature = "serde")]
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+
+use crate::{contains, Distance, Object, Point, Query};
+
+/// A variant of [`KdTree`][crate::KdTree] which stores the splitting axis of each node explicitly
+///
+/// Instead of cycling through the axes depending on the depth of a node, each node is split along the axis stored for it.
+/// This allows construction strategies to choose the axis adaptively, e.g. [`KdTreeBuilder::build_adaptive`][crate::KdTreeBuilder::build_adaptive] splits along the axis of widest spread.
+///
+/// The axes take up one additional byte per object which is why the cyclic [`KdTree`][crate::KdTree] does not store them.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct AdaptiveKdTree<O, S = Box<[O]>>
+where
+    S: AsRef<[O]>,
+{
+    objects: S,
+    axes: Box<[u8]>,
+    _marker: PhantomData<O>,
+}
+
+impl<O, S> AdaptiveKdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]> + AsMut<[O]>,
+{
+    /// Construct a new tree by sorting the given `objects`, cycling through the axes like [`KdTree::new`][crate::KdTree::new] but storing them
+    ///
+    /// The objects are arranged exactly as by [`KdTree::new`][crate::KdTree::new].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the objects' positions exceeds 256.
+    pub fn new(objects: S) -> Self {
+        Self::new_by(objects, |_objects, depth| depth % O::Point::DIM)
+    }
+
+    /// Construct a new tree by sorting the given `objects`, splitting each node along the axis chosen by `choose_axis`
+    ///
+    /// The function `choose_axis` is called with the objects of each subtree before it is split and the depth of its root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimension of the objects' positions exceeds 256 or if `choose_axis` returns an axis which is not smaller than it.
+    pub(crate) fn new_by<F>(mut objects: S, mut choose_axis: F) -> Self
+    where
+        F: FnMut(&[O], usize) -> usize,
+    {
+        assert!(O::Point::DIM <= u8::MAX as usize + 1);
+
+        let mut axes = vec![0; objects.as_ref().len()].into_boxed_slice();
+
+        sort(objects.as_mut(), &mut axes, 0, &mut choose_axis);
+
+        Self {
+            objects,
+            axes,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<O, S> AdaptiveKdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    /// Interprets the given `objects` as a tree split along the given `axes`
+    ///
+    /// Supplying `objects` which are not actually sorted as a k-d tree along the given `axes` is safe but will lead to incorrect results.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of `axes` does not match the number of `objects` or if any of the `axes` is not smaller than the dimension of the objects' positions.
+    pub fn new_unchecked(objects: S, axes: Box<[u8]>) -> Self {
+        if let Err(err) = check_axes(objects.as_ref(), &axes) {
+            panic!("{}", err);
+        }
+
+        Self {
+            objects,
+            axes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the splitting axis of each node in the order of the objects
+    pub fn axes(&self) -> &[u8] {
+        &self.axes
+    }
+
+    /// Find objects matching the given `query`
+    ///
+    /// This works like [`KdTree::look_up`][crate::KdTree::look_up] but reads the splitting axis of each node instead of computing it from its depth.
+    pub fn look_up<'a, Q, V>(&'a self, query: &Q, mut visitor: V) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        look_up(query, &mut visitor, self.objects.as_ref(), &self.axes)
+    }
+
+    /// Find the object nearest to the given `target`
+    ///
+    /// This works like [`KdTree::nearest`][crate::KdTree::nearest] but reads the splitting axis of each node instead of computing it from its depth.
+    pub fn nearest(&self, target: &O::Point) -> Option<&O>
+    where
+        O::Point: Distance,
+        <O::Point as Point>::Coord: Float,
+    {
+        let mut best_match = (<O::Point as Point>::Coord::infinity(), None);
+
+        nearest(target, &mut best_match, self.objects.as_ref(), &self.axes);
+
+        best_match.1
+    }
+}
+
+/// Checks the `axes` against the `objects` as the indexing during traversals relies on them matching
+#[cfg(feature = "serde")]
+impl<'de, O, S> Deserialize<'de> for AdaptiveKdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]> + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "AdaptiveKdTree")]
+        struct Fields<O, S> {
+            objects: S,
+            axes: Box<[u8]>,
+            _marker: PhantomData<O>,
+        }
+
+        let fields = Fields::<O, S>::deserialize(deserializer)?;
+
+        check_axes(fields.objects.as_ref(), &fields.axes).map_err(D::Error::custom)?;
+
+        Ok(Self {
+            objects: fields.objects,
+            axes: fields.axes,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<O, S> Deref for AdaptiveKdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    type Target = [O];
+
+    fn deref(&self) -> &Self::Target {
+        self.objects.as_ref()
+    }
+}
+
+impl<O, S> AsRef<[O]> for AdaptiveKdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    fn as_ref(&self) -> &[O] {
+        self.objects.as_ref()
+    }
+}
+
+fn check_axes<O>(objects: &[O], axes: &[u8]) -> Result<(), String>
+where
+    O: Object,
+{
+    if objects.len() != axes.len() {
+        return Err(format!(
+            "number of axes {} does not match number of objects {}",
+            axes.len(),
+            objects.len()
+        ));
+    }
+
+    if let Some(axis) = axes.iter().find(|axis| **axis as usize >= O::Point::DIM) {
+        return Err(format!(
+            "axis {} is not smaller than dimension {}",
+            axis,
+            O::Point::DIM
+        ));
+    }
+
+    Ok(())
+}
+
+fn sort<O, F>(objects: &mut [O], axes: &mut [u8], depth: usize, choose_axis: &mut F)
+where
+    O: Object,
+    F: FnMut(&[O], usize) -> usize,
+{
+    if objects.len() <= 1 {
+        return;
+    }
+
+    let axis = choose_axis(objects, depth);
+    assert!(axis < O::Point::DIM);
+
+    let mid = objects.len() / 2;
+
+    objects.select_nth_unstable_by(mid, |lhs, rhs| {
+        let lhs = lhs.position().coord(axis);
+        let rhs = rhs.position().coord(axis);
+
+        lhs.partial_cmp(&rhs).unwrap()
+    });
+
+    axes[mid] = axis as u8;
+
+    let (left, right) = objects.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+
+    sort(left, left_axes, depth + 1, choose_axis);
+    sort(
+        &mut right[1..],
+        &mut right_axes[1..],
+        depth + 1,
+        choose_axis,
+    );
+}
+
+fn look_up<'a, O, Q, V>(
+    query: &Q,
+    visitor: &mut V,
+    objects: &'a [O],
+    axes: &[u8],
+) -> ControlFlow<()>
+where
+    O: Object,
+    Q: Query<O::Point>,
+    V: FnMut(&'a O) -> ControlFlow<()>,
+{
+    if objects.is_empty() {
+        return ControlFlow::Continue(());
+    }
+
+    let mid = objects.len() / 2;
+    let object = &objects[mid];
+    let axis = axes[mid] as usize;
+
+    let position = object.position();
+
+    if contains(query.aabb(), position) && query.test(position) {
+        visitor(object)?;
+    }
+
+    if query.aabb().0.coord(axis) <= position.coord(axis) {
+        look_up(query, visitor, &objects[..mid], &axes[..mid])?;
+    }
+
+    if position.coord(axis) <= query.aabb().1.coord(axis) {
+        look_up(query, visitor, &objects[mid + 1..], &axes[mid + 1..])?;
+    }
+
+    ControlFlow::Continue(())
+}
+
+fn nearest<'a, O>(
+    target: &O::Point,
+    best_match: &mut (<O::Point as Point>::Coord, Option<&'a O>),
+    objects: &'a [O],
+    axes: &[u8],
+) where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+{
+    if objects.is_empty() {
+        return;
+    }
+
+    let mid = objects.len() / 2;
+    let object = &objects[mid];
+    let axis = axes[mid] as usize;
+
+    let position = object.position();
+
+    let distance_2 = target.distance_2(position);
+
+    if best_match.0 > distance_2 {
+        *best_match = (distance_2, Some(object));
+    }
+
+    let offset = target.coord(axis) - position.coord(axis);
+    let axis_distance_2 = target.axis_distance_2(axis, position.coord(axis));
+
+    let (left, right) = (
+        (&objects[..mid], &axes[..mid]),
+        (&objects[mid + 1..], &axes[mid + 1..]),
+    );
+
+    let (near, far) = if offset.is_sign_positive() {
+        (right, left)
+    } else {
+        (left, right)
+    };
+
+    nearest(target, best_match, near.0, near.1);
+
+    if best_match.0 > axis_distance_2 {
+        nearest(target, best_match, far.0, far.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::{
+        tests::{
+            assert_look_up_matches_brute_force, assert_nearest_matches_objects, random_objects,
+            random_points, random_queries,
+        },
+        KdTree,
+    };
+
+    #[test]
+    fn random_adaptive_new_cycles_axes() {
+        fn check_axes(axes: &[u8], axis: u8) {
+            if axes.len() <= 1 {
+                return;
+            }
+
+            let mid = axes.len() / 2;
+            assert_eq!(axes[mid], axis);
+
+            check_axes(&axes[..mid], (axis + 1) % 2);
+            check_axes(&axes[mid + 1..], (axis + 1) % 2);
+        }
+
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index1 = KdTree::new(objects.clone());
+                let index2 = AdaptiveKdTree::new(objects);
+
+                assert_eq!(&*index1, &*index2);
+                check_axes(index2.axes(), 0);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_adaptive_look_up() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = AdaptiveKdTree::new(objects);
+
+                    for query in queries {
+                        assert_look_up_matches_brute_force(&index, &query, |visitor| {
+                            index.look_up(&query, visitor)
+                        });
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_adaptive_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = AdaptiveKdTree::new(objects);
+
+                    for target in targets {
+                        assert_nearest_matches_objects(&index, &target, index.nearest(&target));
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "axis 2 is not smaller than dimension 2")]
+    fn new_unchecked_rejects_invalid_axis() {
+        AdaptiveKdTree::new_unchecked(vec![[0.0, 0.0], [1.0, 1.0]], vec![0, 2].into());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_checks_axes() {
+        let index = AdaptiveKdTree::new(vec![[0.0, 1.0], [2.0, 3.0], [4.0, 5.0]]);
+
+        let json = serde_json::to_string(&index).unwrap();
+
+        let index = serde_json::from_str::<AdaptiveKdTree<[f64; 2], Vec<_>>>(&json).unwrap();
+        assert_eq!(index.len(), 3);
+
+        let err = serde_json::from_str::<AdaptiveKdTree<[f64; 2], Vec<_>>>(
+            r#"{"objects":[[0.0,1.0],[2.0,3.0]],"axes":[0],"_marker":null}"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("number of axes 1 does not match number of objects 2"));
+
+        let err = serde_json::from_str::<AdaptiveKdTree<[f64; 2], Vec<_>>>(
+            r#"{"objects":[[0.0,1.0],[2.0,3.0]],"axes":[0,7],"_marker":null}"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("axis 7 is not smaller than dimension 2"));
+    }
+}
@@ -0,0 +1,412 @@
+use std::ops::ControlFlow;
+
+use num_traits::{Float, Zero};
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::{contains, split, Distance, KdTree, Object, Point, Query, WithinDistance};
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    #[cfg(feature = "rand")]
+    /// Draw a single object uniformly at random from those matching the given `query`
+    ///
+    /// Uses reservoir sampling with a reservoir of size one, i.e. the matches are found in a single traversal without allocating memory.
+    /// Each of the `n` matching objects is returned with probability `1/n` independently of the [order of traversal][Self::look_up].
+    ///
+    /// Returns `None` if no object matches the `query`.
+    ///
+    /// Requires the `rand` feature.
+    pub fn look_up_sample_one<Q, R>(&self, query: &Q, rng: &mut R) -> Option<&O>
+    where
+        Q: Query<O::Point>,
+        R: Rng + ?Sized,
+    {
+        let mut count = 0;
+        let mut sample = None;
+
+        let _ = self.look_up(query, |object| {
+            count += 1;
+
+            if rng.gen_range(0..count) == 0 {
+                sample = Some(object);
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        sample
+    }
+
+    /// Estimate the number of objects matching the given `query` by testing at most `samples` objects
+    ///
+    /// The search descends like [`look_up`][Self::look_up] but splits the remaining budget of `samples` between the subtrees which need to be searched proportionally to their sizes.
+    /// Subtrees which cannot be searched anymore are extrapolated from whether their parent matched, assuming that objects are distributed evenly within them.
+    ///
+    /// The estimate is exact if `samples` is at least the number of objects in the tree.
+    /// Otherwise, its accuracy depends on how uniformly the matching objects are distributed and is usually rather coarse, making it suitable only for deciding between strategies.
+    pub fn estimate_count<Q>(&self, query: &Q, samples: usize) -> usize
+    where
+        Q: Query<O::Point>,
+    {
+        let objects = self.objects.as_ref();
+
+        if objects.is_empty() || samples == 0 {
+            return 0;
+        }
+
+        estimate_count(query, objects, 0, samples).round() as usize
+    }
+}
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+    S: AsRef<[O]>,
+{
+    /// Find the medoid of the objects matching the given `query`, i.e. the match minimizing the sum of its distances to all other matches
+    ///
+    /// The distances are the square roots of [`Distance::distance_2`] and ties are broken in favour of the match found first.
+    /// All matches are collected and their pairwise distances computed, so this takes `O(m²)` time and `O(m)` memory for `m` matches.
+    ///
+    /// Returns `None` if no object matches the `query`.
+    pub fn region_medoid<Q>(&self, query: &Q) -> Option<&O>
+    where
+        Q: Query<O::Point>,
+    {
+        let mut matches = Vec::new();
+
+        let _ = self.look_up(query, |object| {
+            matches.push(object);
+
+            ControlFlow::Continue(())
+        });
+
+        let mut medoid = None;
+        let mut min_sum = <O::Point as Point>::Coord::infinity();
+
+        for candidate in &matches {
+            let sum = matches
+                .iter()
+                .fold(<O::Point as Point>::Coord::zero(), |sum, object| {
+                    sum + candidate.position().distance_2(object.position()).sqrt()
+                });
+
+            if medoid.is_none() || min_sum > sum {
+                medoid = Some(*candidate);
+                min_sum = sum;
+            }
+        }
+
+        medoid
+    }
+}
+
+fn estimate_count<O, Q>(query: &Q, objects: &[O], axis: usize, samples: usize) -> f64
+where
+    O: Object,
+    Q: Query<O::Point>,
+{
+    let (left, object, right) = split(objects);
+
+    let position = object.position();
+
+    let matched = contains(query.aabb(), position) && query.test(position);
+
+    let mut count = if matched { 1.0 } else { 0.0 };
+
+    let left = if !left.is_empty() && query.aabb().0.coord(axis) <= position.coord(axis) {
+        left
+    } else {
+        &[]
+    };
+
+    let right = if !right.is_empty() && position.coord(axis) <= query.aabb().1.coord(axis) {
+        right
+    } else {
+        &[]
+    };
+
+    let next_axis = (axis + 1) % O::Point::DIM;
+
+    let samples = samples - 1;
+    let left_samples = if left.is_empty() {
+        0
+    } else {
+        samples * left.len() / (left.len() + right.len())
+    };
+    let right_samples = samples - left_samples;
+
+    for (objects, samples) in [(left, left_samples), (right, right_samples)] {
+        if objects.is_empty() {
+            continue;
+        }
+
+        if samples != 0 {
+            count += estimate_count(query, objects, next_axis, samples);
+        } else if matched {
+            count += objects.len() as f64;
+        }
+    }
+
+    count
+}
+
+impl<O, S, T, const N: usize> KdTree<O, S>
+where
+    O: Object<Point = [T; N]>,
+    T: Float,
+    S: AsRef<[O]>,
+{
+    /// Compute the weighted centroid of the objects matching the given `query`
+    ///
+    /// The positions of all matching objects are averaged using the given `weight` in a single traversal without collecting the matches.
+    ///
+    /// Returns `None` if no objects match or if their weights sum to zero.
+    pub fn region_centroid<Q, W>(&self, query: &Q, weight: W) -> Option<[T; N]>
+    where
+        Q: Query<[T; N]>,
+        W: Fn(&O) -> T,
+    {
+        let mut sum = [T::zero(); N];
+        let mut total_weight = T::zero();
+
+        let _ = self.look_up(query, |object| {
+            let weight = weight(object);
+            let position = object.position();
+
+            for axis in 0..N {
+                sum[axis] = sum[axis] + weight * position[axis];
+            }
+
+            total_weight = total_weight + weight;
+
+            ControlFlow::Continue(())
+        });
+
+        if total_weight == T::zero() {
+            return None;
+        }
+
+        Some(sum.map(|sum| sum / total_weight))
+    }
+
+    /// Count the objects within each of the given `radii` around `center`
+    ///
+    /// The `radii` must be sorted in ascending order and the result contains the number of objects within the Euclidean distance given by each radius.
+    /// All counts are determined in a single traversal of the tree using the largest radius,
+    /// which is more efficient than counting each radius separately, e.g. when computing Ripley's K function.
+    pub fn count_radii(&self, center: [T; N], radii: &[T]) -> Vec<usize> {
+        let mut counts = vec![0; radii.len()];
+
+        let max_radius = match radii.last() {
+            Some(max_radius) => *max_radius,
+            None => return counts,
+        };
+
+        let radii_2 = radii
+            .iter()
+            .map(|radius| *radius * *radius)
+            .collect::<Vec<_>>();
+
+        let _ = self.look_up(&WithinDistance::new(center, max_radius), |object| {
+            let distance_2 = center.distance_2(object.position());
+
+            let radius = radii_2.partition_point(|radius_2| *radius_2 < distance_2);
+            counts[radius] += 1;
+
+            ControlFlow::Continue(())
+        });
+
+        for radius in 1..counts.len() {
+            counts[radius] += counts[radius - 1];
+        }
+
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+    #[cfg(feature = "rand")]
+    use rand::rngs::mock::StepRng;
+
+    use crate::tests::{random_objects, random_points, random_queries, RandomObject};
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_look_up_sample_one() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut last_match = None;
+                        let _ = index.look_up(&query, |object| {
+                            last_match = Some(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        // A constant zero replaces the sample by each match, i.e. the last one is returned.
+                        let sample = index.look_up_sample_one(&query, &mut StepRng::new(0, 0));
+
+                        assert_eq!(last_match, sample);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_region_medoid() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let matches = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let sum = |candidate: &RandomObject| {
+                            matches
+                                .iter()
+                                .map(|object| candidate.0.distance_2(&object.0).sqrt())
+                                .sum::<f32>()
+                        };
+
+                        match index.region_medoid(&query) {
+                            Some(medoid) => {
+                                let min_sum = matches
+                                    .iter()
+                                    .map(|candidate| sum(candidate))
+                                    .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                                    .unwrap();
+
+                                assert!(query.test(medoid.position()));
+                                assert!((sum(medoid) - min_sum).abs() <= 1e-3 * (1.0 + min_sum));
+                            }
+                            None => assert!(matches.is_empty()),
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_estimate_count() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let count = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .count();
+
+                        assert_eq!(index.estimate_count(&query, 100), count);
+                        assert!(index.estimate_count(&query, 10) <= 100);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_region_centroid() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let matches = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let result = index.region_centroid(&query, |_object| 1.0);
+
+                        if matches.is_empty() {
+                            assert_eq!(result, None);
+                        } else {
+                            let result = result.unwrap();
+
+                            for (axis, result) in result.iter().enumerate() {
+                                let expected =
+                                    matches.iter().map(|object| object.0[axis]).sum::<f32>()
+                                        / matches.len() as f32;
+
+                                assert!((result - expected).abs() < 1e-4);
+                            }
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_count_radii() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_points(10),
+                    vec(0.0_f32..=1.0, 5),
+                ),
+                |(objects, centers, mut radii)| {
+                    let index = KdTree::new(objects);
+
+                    radii.sort_unstable_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+                    for center in centers {
+                        let counts1 = radii
+                            .iter()
+                            .map(|radius| {
+                                index
+                                    .iter()
+                                    .filter(|object| {
+                                        object.0.distance_2(&center) <= radius * radius
+                                    })
+                                    .count()
+                            })
+                            .collect::<Vec<_>>();
+
+                        let counts2 = index.count_radii(center, &radii);
+
+                        assert_eq!(counts1, counts2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Deref};
+
+use crate::{look_up::look_up_at_axis, sort::sort, Object, Point, Query};
+
+/// A variant of [`KdTree`][crate::KdTree] whose root is split along a chosen start axis instead of the first one
+///
+/// The axes are still cycled through depending on the depth of a node, but starting from the stored start axis.
+/// Since all methods of [`KdTree`][crate::KdTree] assume that the root is split along the first axis, this is a separate type which only supports [`look_up_from_axis`][Self::look_up_from_axis].
+#[derive(Debug, Default, Clone)]
+pub struct KdTreeAt<O, S = Box<[O]>>
+where
+    S: AsRef<[O]>,
+{
+    objects: S,
+    start_axis: usize,
+    _marker: PhantomData<O>,
+}
+
+impl<O, S> KdTreeAt<O, S>
+where
+    O: Object,
+    S: AsRef<[O]> + AsMut<[O]>,
+{
+    /// Construct a new tree by sorting the given `objects`, splitting the root along `start_axis`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start_axis` is not smaller than the dimension of the objects' positions.
+    pub fn new(mut objects: S, start_axis: usize) -> Self {
+        assert!(start_axis < O::Point::DIM);
+
+        sort(objects.as_mut(), start_axis);
+
+        Self {
+            objects,
+            start_axis,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<O, S> KdTreeAt<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    /// Interprets the given `objects` as a tree whose root is split along `start_axis`
+    ///
+    /// Supplying `objects` which are not actually sorted as such a tree is safe but will lead to incorrect results.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start_axis` is not smaller than the dimension of the objects' positions.
+    pub fn new_unchecked(objects: S, start_axis: usize) -> Self {
+        assert!(start_axis < O::Point::DIM);
+
+        Self {
+            objects,
+            start_axis,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the axis along which the root of this tree is split
+    pub fn start_axis(&self) -> usize {
+        self.start_axis
+    }
+
+    /// Find objects matching the given `query`
+    ///
+    /// This works like [`KdTree::look_up`][crate::KdTree::look_up] but starts cycling through the axes at the [start axis][Self::start_axis] of this tree.
+    pub fn look_up_from_axis<'a, Q, V>(&'a self, query: &Q, visitor: V) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        look_up_at_axis(self.objects.as_ref(), self.start_axis, query, visitor)
+    }
+
+    /// Return the backing storage of this tree
+    pub fn into_storage(self) -> S {
+        self.objects
+    }
+}
+
+impl<O, S> Deref for KdTreeAt<O, S>
+where
+    S: AsRef<[O]>,
+{
+    type Target = [O];
+
+    fn deref(&self) -> &Self::Target {
+        self.objects.as_ref()
+    }
+}
+
+impl<O, S> AsRef<[O]> for KdTreeAt<O, S>
+where
+    S: AsRef<[O]>,
+{
+    fn as_ref(&self) -> &[O] {
+        self.objects.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::tests::{assert_look_up_matches_brute_force, random_objects, random_queries};
+
+    #[test]
+    fn random_look_up_from_axis() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), 0_usize..2),
+                |(objects, queries, start_axis)| {
+                    let index = KdTreeAt::new(objects, start_axis);
+
+                    for query in queries {
+                        assert_look_up_matches_brute_force(&index, &query, |visitor| {
+                            index.look_up_from_axis(&query, visitor)
+                        });
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_unchecked_rejects_invalid_start_axis() {
+        KdTreeAt::new_unchecked(vec![[0.0_f32, 0.0]], 2);
+    }
+}
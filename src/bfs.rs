@@ -0,0 +1,316 @@
+use std::marker::PhantomData;
+use std::mem::swap;
+use std::ops::{ControlFlow, Deref};
+
+use num_traits::Float;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{contains, sort::permute, Distance, Object, Point, Query};
+
+/// A variant of [`KdTree`][crate::KdTree] which lays out its nodes in breadth-first order
+///
+/// The children of the node at index `i` are stored at the indices `2 * i + 1` and `2 * i + 2` like in a binary heap,
+/// also known as the [Eytzinger layout](https://arxiv.org/abs/1509.05053). Hence the nodes near the root which are visited by every query are packed together
+/// at the start of the storage and the location of the children is known without inspecting the node, which makes prefetching them effective.
+///
+/// To keep the layout compact, each node is split such that the tree is complete, i.e. all levels except the last one are full and the last one is filled from the left.
+/// Hence the split is not exactly at the median and the layout is not compatible with the one of [`KdTree`][crate::KdTree].
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct BfsKdTree<O, S = Box<[O]>>
+where
+    S: AsRef<[O]>,
+{
+    objects: S,
+    _marker: PhantomData<O>,
+}
+
+impl<O, S> BfsKdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]> + AsMut<[O]>,
+{
+    /// Construct a new tree by sorting the given `objects` into breadth-first order
+    ///
+    /// Note that determining the layout requires additional temporary memory of two `usize` per object.
+    pub fn new(mut objects: S) -> Self {
+        let objects_mut = objects.as_mut();
+
+        let len = objects_mut.len();
+
+        let mut indices = (0..len).collect::<Vec<_>>();
+        let mut permutation = vec![0; len];
+
+        arrange(objects_mut, &mut indices, &mut permutation, 0, 0);
+
+        permute(objects_mut, &permutation);
+
+        Self {
+            objects,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<O, S> BfsKdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    /// Interprets the given `objects` as a tree in breadth-first order
+    ///
+    /// Supplying `objects` which are not actually sorted as such a tree is safe but will lead to incorrect results.
+    pub fn new_unchecked(objects: S) -> Self {
+        Self {
+            objects,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Find objects matching the given `query`
+    ///
+    /// This works like [`KdTree::look_up`][crate::KdTree::look_up] but computes the indices of the children instead of splitting the storage.
+    pub fn look_up<'a, Q, V>(&'a self, query: &Q, mut visitor: V) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        look_up(query, &mut visitor, self.objects.as_ref(), 0, 0)
+    }
+
+    /// Find the object nearest to the given `target`
+    ///
+    /// This works like [`KdTree::nearest`][crate::KdTree::nearest] but computes the indices of the children instead of splitting the storage.
+    pub fn nearest(&self, target: &O::Point) -> Option<&O>
+    where
+        O::Point: Distance,
+        <O::Point as Point>::Coord: Float,
+    {
+        let mut best_match = (<O::Point as Point>::Coord::infinity(), None);
+
+        nearest(target, &mut best_match, self.objects.as_ref(), 0, 0);
+
+        best_match.1
+    }
+}
+
+impl<O, S> Deref for BfsKdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    type Target = [O];
+
+    fn deref(&self) -> &Self::Target {
+        self.objects.as_ref()
+    }
+}
+
+impl<O, S> AsRef<[O]> for BfsKdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    fn as_ref(&self) -> &[O] {
+        self.objects.as_ref()
+    }
+}
+
+/// Computes the number of nodes in the subtree rooted at `node` of a complete tree with `len` nodes
+fn subtree_len(node: usize, len: usize) -> usize {
+    let mut subtree_len = 0;
+
+    let mut first = node;
+    let mut width = 1;
+
+    while first < len {
+        subtree_len += width.min(len - first);
+
+        first = 2 * first + 1;
+        width *= 2;
+    }
+
+    subtree_len
+}
+
+/// Determines the original index of the object placed at each node of the subtree rooted at `node`
+fn arrange<O>(
+    objects: &[O],
+    indices: &mut [usize],
+    permutation: &mut [usize],
+    node: usize,
+    axis: usize,
+) where
+    O: Object,
+{
+    if indices.is_empty() {
+        return;
+    }
+
+    let left_len = subtree_len(2 * node + 1, permutation.len());
+
+    let (left, mid, right) = indices.select_nth_unstable_by(left_len, |lhs, rhs| {
+        let lhs = objects[*lhs].position().coord(axis);
+        let rhs = objects[*rhs].position().coord(axis);
+
+        lhs.partial_cmp(&rhs).unwrap()
+    });
+
+    permutation[node] = *mid;
+
+    let next_axis = (axis + 1) % O::Point::DIM;
+
+    arrange(objects, left, permutation, 2 * node + 1, next_axis);
+    arrange(objects, right, permutation, 2 * node + 2, next_axis);
+}
+
+fn look_up<'a, O, Q, V>(
+    query: &Q,
+    visitor: &mut V,
+    objects: &'a [O],
+    node: usize,
+    axis: usize,
+) -> ControlFlow<()>
+where
+    O: Object,
+    Q: Query<O::Point>,
+    V: FnMut(&'a O) -> ControlFlow<()>,
+{
+    let object = match objects.get(node) {
+        Some(object) => object,
+        None => return ControlFlow::Continue(()),
+    };
+
+    let position = object.position();
+
+    if contains(query.aabb(), position) && query.test(position) {
+        visitor(object)?;
+    }
+
+    let next_axis = (axis + 1) % O::Point::DIM;
+
+    if query.aabb().0.coord(axis) <= position.coord(axis) {
+        look_up(query, visitor, objects, 2 * node + 1, next_axis)?;
+    }
+
+    if position.coord(axis) <= query.aabb().1.coord(axis) {
+        look_up(query, visitor, objects, 2 * node + 2, next_axis)?;
+    }
+
+    ControlFlow::Continue(())
+}
+
+fn nearest<'a, O>(
+    target: &O::Point,
+    best_match: &mut (<O::Point as Point>::Coord, Option<&'a O>),
+    objects: &'a [O],
+    node: usize,
+    axis: usize,
+) where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+{
+    let object = match objects.get(node) {
+        Some(object) => object,
+        None => return,
+    };
+
+    let position = object.position();
+
+    let distance_2 = target.distance_2(position);
+
+    if best_match.0 > distance_2 {
+        *best_match = (distance_2, Some(object));
+    }
+
+    let offset = target.coord(axis) - position.coord(axis);
+    let axis_distance_2 = target.axis_distance_2(axis, position.coord(axis));
+
+    let (mut near, mut far) = (2 * node + 1, 2 * node + 2);
+
+    if offset.is_sign_positive() {
+        swap(&mut near, &mut far);
+    }
+
+    let next_axis = (axis + 1) % O::Point::DIM;
+
+    nearest(target, best_match, objects, near, next_axis);
+
+    if best_match.0 > axis_distance_2 {
+        nearest(target, best_match, objects, far, next_axis);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{strategy::Strategy, test_runner::TestRunner};
+
+    use crate::tests::{
+        assert_look_up_matches_brute_force, assert_nearest_matches_objects, random_objects,
+        random_points, random_queries,
+    };
+
+    #[test]
+    fn subtree_len_matches_recursion() {
+        fn recursive(node: usize, len: usize) -> usize {
+            if node >= len {
+                0
+            } else {
+                1 + recursive(2 * node + 1, len) + recursive(2 * node + 2, len)
+            }
+        }
+
+        for len in 0..100 {
+            for node in 0..len + 2 {
+                assert_eq!(subtree_len(node, len), recursive(node, len));
+            }
+        }
+    }
+
+    #[test]
+    fn random_bfs_look_up() {
+        TestRunner::default()
+            .run(
+                &(
+                    (0_usize..100).prop_flat_map(random_objects),
+                    random_queries(10),
+                ),
+                |(objects, queries)| {
+                    let index = BfsKdTree::new(objects);
+
+                    for query in queries {
+                        assert_look_up_matches_brute_force(&index, &query, |visitor| {
+                            index.look_up(&query, visitor)
+                        });
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_bfs_nearest() {
+        TestRunner::default()
+            .run(
+                &(
+                    (1_usize..100).prop_flat_map(random_objects),
+                    random_points(10),
+                ),
+                |(objects, targets)| {
+                    let index = BfsKdTree::new(objects);
+
+                    for target in targets {
+                        assert_nearest_matches_objects(&index, &target, index.nearest(&target));
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,301 @@
+use std::marker::PhantomData;
+use std::ops::{ControlFlow, Deref};
+
+use num_traits::Float;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    look_up::look_up_buckets, nearest::nearest_slice_by_metric, sort::sort_axis, AdaptiveKdTree,
+    Distance, Euclidean, Object, Point, Query,
+};
+
+/// Configures the construction of trees beyond the defaults used by [`KdTree::new`][crate::KdTree::new]
+///
+/// ```
+/// use sif_kdtree::KdTreeBuilder;
+///
+/// let index = KdTreeBuilder::new()
+///     .leaf_size(16)
+///     .build(vec![[0.0, 1.0], [2.0, 3.0], [4.0, 5.0]]);
+///
+/// assert_eq!(index.nearest(&[1.0, 1.0]), Some(&[0.0, 1.0]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct KdTreeBuilder {
+    leaf_size: usize,
+}
+
+impl Default for KdTreeBuilder {
+    fn default() -> Self {
+        Self { leaf_size: 1 }
+    }
+}
+
+impl KdTreeBuilder {
+    /// Construct a builder using the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of objects up to which subtrees are left unsorted
+    ///
+    /// Such subtrees form buckets which are scanned linearly during queries, trading comparisons for locality.
+    /// The default of one yields the same layout as [`KdTree::new`][crate::KdTree::new].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `leaf_size` is zero.
+    pub fn leaf_size(mut self, leaf_size: usize) -> Self {
+        assert_ne!(leaf_size, 0);
+
+        self.leaf_size = leaf_size;
+        self
+    }
+
+    /// Construct a new tree by sorting the given `objects`, splitting each node along the axis on which its objects have the widest spread
+    ///
+    /// This suits anisotropic data whose coordinate values have very different ranges along different axes, for which cycling through the axes yields badly shaped nodes.
+    /// The flat layout of [`KdTree`][crate::KdTree] derives the splitting axis from the depth of a node and has no room to store it,
+    /// and recomputing the spread during queries would require visiting all objects of each subtree.
+    /// Hence the chosen axes are recorded in an [`AdaptiveKdTree`] at the cost of one additional byte per object.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`leaf_size`][Self::leaf_size] other than one was configured as adaptive trees do not support buckets,
+    /// or if the dimension of the objects' positions exceeds 256.
+    pub fn build_adaptive<O, S>(self, objects: S) -> AdaptiveKdTree<O, S>
+    where
+        O: Object,
+        S: AsRef<[O]> + AsMut<[O]>,
+    {
+        assert_eq!(self.leaf_size, 1);
+
+        AdaptiveKdTree::new_by(objects, |objects, _depth| widest_axis(objects))
+    }
+
+    /// Construct a new tree by sorting the given `objects` according to this configuration
+    pub fn build<O, S>(self, mut objects: S) -> BucketedKdTree<O, S>
+    where
+        O: Object,
+        S: AsRef<[O]> + AsMut<[O]>,
+    {
+        sort(objects.as_mut(), 0, self.leaf_size);
+
+        BucketedKdTree {
+            objects,
+            leaf_size: self.leaf_size,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// A variant of [`KdTree`][crate::KdTree] whose subtrees of up to [`leaf_size`][KdTreeBuilder::leaf_size] objects are left unsorted
+///
+/// The layout is the same as the one of [`KdTree`][crate::KdTree] except that the recursion stops at the buckets, which are scanned linearly during queries.
+/// Since the buckets are not sorted, the objects of such a tree must not be interpreted as a [`KdTree`][crate::KdTree] unless the leaf size is one.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BucketedKdTree<O, S = Box<[O]>>
+where
+    S: AsRef<[O]>,
+{
+    objects: S,
+    leaf_size: usize,
+    _marker: PhantomData<O>,
+}
+
+impl<O, S> BucketedKdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    /// Return the number of objects up to which subtrees are left unsorted
+    pub fn leaf_size(&self) -> usize {
+        self.leaf_size
+    }
+
+    /// Find objects matching the given `query`
+    ///
+    /// This works like [`KdTree::look_up`][crate::KdTree::look_up] but scans the buckets linearly.
+    pub fn look_up<'a, Q, V>(&'a self, query: &Q, visitor: V) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        look_up_buckets(self.objects.as_ref(), 0, self.leaf_size, query, visitor)
+    }
+
+    /// Find the object nearest to the given `target`
+    ///
+    /// This works like [`KdTree::nearest`][crate::KdTree::nearest] but scans the buckets linearly.
+    pub fn nearest(&self, target: &O::Point) -> Option<&O>
+    where
+        O::Point: Distance,
+        <O::Point as Point>::Coord: Float,
+    {
+        nearest_slice_by_metric(self.objects.as_ref(), target, &Euclidean, self.leaf_size)
+    }
+}
+
+impl<O, S> Deref for BucketedKdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    type Target = [O];
+
+    fn deref(&self) -> &Self::Target {
+        self.objects.as_ref()
+    }
+}
+
+impl<O, S> AsRef<[O]> for BucketedKdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    fn as_ref(&self) -> &[O] {
+        self.objects.as_ref()
+    }
+}
+
+fn sort<O>(objects: &mut [O], axis: usize, leaf_size: usize)
+where
+    O: Object,
+{
+    if objects.len() <= leaf_size {
+        return;
+    }
+
+    let (left, right, next_axis) = sort_axis(objects, axis);
+
+    sort(left, next_axis, leaf_size);
+    sort(right, next_axis, leaf_size);
+}
+
+fn widest_axis<O>(objects: &[O]) -> usize
+where
+    O: Object,
+{
+    let spread = |axis| {
+        let first = objects[0].position().coord(axis);
+
+        let (min, max) = objects.iter().fold((first, first), |(min, max), object| {
+            let coord = object.position().coord(axis);
+
+            if coord < min {
+                (coord, max)
+            } else if max < coord {
+                (min, coord)
+            } else {
+                (min, max)
+            }
+        });
+
+        max - min
+    };
+
+    (0..O::Point::DIM)
+        .map(|axis| (axis, spread(axis)))
+        .max_by(|(_, lhs), (_, rhs)| lhs.partial_cmp(rhs).unwrap())
+        .unwrap()
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::{
+        tests::{
+            assert_look_up_matches_brute_force, assert_nearest_matches_objects, random_objects,
+            random_points, random_queries, RandomObject,
+        },
+        KdTree,
+    };
+
+    #[test]
+    fn random_bucketed_look_up() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), 1_usize..20),
+                |(objects, queries, leaf_size)| {
+                    let index = KdTreeBuilder::new().leaf_size(leaf_size).build(objects);
+
+                    for query in queries {
+                        assert_look_up_matches_brute_force(&index, &query, |visitor| {
+                            index.look_up(&query, visitor)
+                        });
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_bucketed_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 1_usize..20),
+                |(objects, targets, leaf_size)| {
+                    let index = KdTreeBuilder::new().leaf_size(leaf_size).build(objects);
+
+                    for target in targets {
+                        assert_nearest_matches_objects(&index, &target, index.nearest(&target));
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_build_adaptive() {
+        fn check_axes(objects: &[RandomObject], axes: &[u8]) {
+            if objects.len() <= 1 {
+                return;
+            }
+
+            let mid = objects.len() / 2;
+            assert_eq!(axes[mid] as usize, widest_axis(objects));
+
+            check_axes(&objects[..mid], &axes[..mid]);
+            check_axes(&objects[mid + 1..], &axes[mid + 1..]);
+        }
+
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTreeBuilder::new().build_adaptive(objects);
+
+                    check_axes(&index, index.axes());
+
+                    for target in targets {
+                        assert_nearest_matches_objects(&index, &target, index.nearest(&target));
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_default_leaf_size() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index1 = KdTree::new(objects.clone());
+                let index2 = KdTreeBuilder::new().build(objects);
+
+                assert_eq!(&*index1, &*index2);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
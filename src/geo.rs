@@ -0,0 +1,368 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use num_traits::Float;
+
+use crate::{Distance, Metric, Point, Query};
+
+/// A query which yields all objects within a geographic bounding box which may wrap around the antimeridian
+///
+/// Positions are expected as `[longitude, latitude]` in degrees with longitudes in the range from -180° to 180°.
+///
+/// If the western boundary is east of the eastern boundary, the box is taken to cross the antimeridian, e.g. from 170° to -170°.
+/// In this case, its AABB spans all longitudes so that the search is pruned only by latitude and the [test][Query::test] checks the longitude cyclically.
+///
+/// Note that the box is a rectangle in longitude and latitude and the poles are not treated specially,
+/// i.e. a box touching a pole does not contain all positions at the pole, but only those with longitudes inside the box.
+#[derive(Debug)]
+pub struct GeoBoundingBox<T> {
+    aabb: ([T; 2], [T; 2]),
+    west: T,
+    east: T,
+}
+
+impl<T> GeoBoundingBox<T>
+where
+    T: Float,
+{
+    /// Construct a query from the south-western corner `lower` and the north-eastern corner `upper`, each given as `[longitude, latitude]`
+    pub fn new(lower: [T; 2], upper: [T; 2]) -> Self {
+        let [west, south] = lower;
+        let [east, north] = upper;
+
+        let aabb = if west <= east {
+            ([west, south], [east, north])
+        } else {
+            let half_turn = T::from(180).unwrap();
+
+            ([-half_turn, south], [half_turn, north])
+        };
+
+        Self { aabb, west, east }
+    }
+
+    /// Check whether this box crosses the antimeridian
+    pub fn wraps(&self) -> bool {
+        self.west > self.east
+    }
+}
+
+impl<T> Query<[T; 2]> for GeoBoundingBox<T>
+where
+    T: Float,
+{
+    fn aabb(&self) -> &([T; 2], [T; 2]) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &[T; 2]) -> bool {
+        !self.wraps() || self.west <= position[0] || position[0] <= self.east
+    }
+}
+
+/// A geographic position on the unit sphere using the [great-circle distance](https://en.wikipedia.org/wiki/Great-circle_distance)
+///
+/// The [distance][Distance::distance_2] is the central angle in radians computed using the [haversine formula](https://en.wikipedia.org/wiki/Haversine_formula) and squared so that it can be used for nearest neighbour search like Euclidean distance.
+/// It is correct across the antimeridian and for antipodal positions.
+///
+/// The tree is still split by longitude and latitude, so subtrees are pruned using the angular distance to the meridians or parallels bounding them.
+///
+/// Since the nearest position does not depend on the radius of the sphere, distances can be scaled by it afterwards.
+/// To search plain `[longitude, latitude]` positions with distances on a sphere of a given radius, use [`GreatCircle`] with [`KdTree::nearest_by_metric`][crate::KdTree::nearest_by_metric].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    /// The position as `[longitude, latitude]` in degrees with longitudes in the range from -180° to 180°
+    pub position: [f64; 2],
+}
+
+impl Point for GeoPoint {
+    const DIM: usize = 2;
+
+    type Coord = f64;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self.position[axis]
+    }
+}
+
+/// Squared central angle in radians
+impl Distance for GeoPoint {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        let angle = central_angle(self.position, other.position);
+
+        angle * angle
+    }
+
+    fn axis_distance_2(&self, axis: usize, coord: Self::Coord) -> Self::Coord {
+        let angle = axis_angle(self.position, axis, coord);
+
+        angle * angle
+    }
+}
+
+/// The [great-circle distance](https://en.wikipedia.org/wiki/Great-circle_distance) on a sphere of the given radius for `[longitude, latitude]` positions in degrees
+///
+/// This computes the same distances as [`GeoPoint`] scaled by the radius, which is chosen per search instead of being the unit sphere.
+#[derive(Debug, Clone, Copy)]
+pub struct GreatCircle {
+    /// The radius of the sphere
+    pub radius: f64,
+}
+
+/// Squared great-circle distance
+impl Metric<[f64; 2]> for GreatCircle {
+    fn distance(&self, lhs: &[f64; 2], rhs: &[f64; 2]) -> f64 {
+        let distance = self.radius * central_angle(*lhs, *rhs);
+
+        distance * distance
+    }
+
+    fn axis_distance(&self, target: &[f64; 2], axis: usize, coord: f64) -> f64 {
+        let distance = self.radius * axis_angle(*target, axis, coord);
+
+        distance * distance
+    }
+}
+
+/// Computes the central angle between the given positions in degrees using the haversine formula
+fn central_angle(lhs: [f64; 2], rhs: [f64; 2]) -> f64 {
+    let [lon1, lat1] = lhs.map(f64::to_radians);
+    let [lon2, lat2] = rhs.map(f64::to_radians);
+
+    let sin_lat = ((lat2 - lat1) / 2.0).sin();
+    let sin_lon = ((lon2 - lon1) / 2.0).sin();
+
+    let haversine = sin_lat * sin_lat + lat1.cos() * lat2.cos() * sin_lon * sin_lon;
+
+    2.0 * haversine.sqrt().min(1.0).asin()
+}
+
+/// Computes a lower bound of the central angle between the given position and any position whose coordinate value along `axis` is `coord` or beyond
+fn axis_angle(position: [f64; 2], axis: usize, coord: f64) -> f64 {
+    let angle = if axis == 0 {
+        // Positions beyond the meridian at `coord` form a lune bounded by that meridian and the antimeridian.
+        meridian_angle(position, coord).min(meridian_angle(position, 180.0))
+    } else {
+        (position[1] - coord).abs().to_radians()
+    };
+
+    // Shrink the bound slightly so that rounding does not make it exceed the actual distance.
+    angle * (1.0 - 1e-9)
+}
+
+/// Computes the angular distance of the given position to the meridian at the given longitude, both in degrees
+fn meridian_angle([lon, lat]: [f64; 2], meridian: f64) -> f64 {
+    let lat = lat.to_radians();
+
+    let diff = (lon - meridian).to_radians().abs() % (2.0 * PI);
+    let diff = diff.min(2.0 * PI - diff);
+
+    if diff <= FRAC_PI_2 {
+        (diff.sin() * lat.cos()).asin()
+    } else {
+        // The closest point of the meridian is the pole of the position's hemisphere.
+        FRAC_PI_2 - lat.abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::ops::ControlFlow;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    use crate::{tests::assert_nearest_matches_brute_force, KdTree, Object};
+
+    struct Place([f64; 2]);
+
+    impl Object for Place {
+        type Point = [f64; 2];
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn random_geo_bounding_box() {
+        TestRunner::default()
+            .run(
+                &(
+                    vec((-180.0..=180.0, -90.0..=90.0), 100),
+                    vec(
+                        (-180.0..=180.0, -180.0..=180.0, -90.0..=90.0, -90.0..=90.0),
+                        10,
+                    ),
+                ),
+                |(places, boxes)| {
+                    let index = KdTree::new(
+                        places
+                            .into_iter()
+                            .map(|(lon, lat)| Place([lon, lat]))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    for (west, east, south, north) in boxes {
+                        let (south, north) = if south <= north {
+                            (south, north)
+                        } else {
+                            (north, south)
+                        };
+
+                        let query = GeoBoundingBox::new([west, south], [east, north]);
+
+                        let results1 = index
+                            .iter()
+                            .filter(|place| {
+                                let [lon, lat] = place.0;
+
+                                let lon_inside = if west <= east {
+                                    west <= lon && lon <= east
+                                } else {
+                                    west <= lon || lon <= east
+                                };
+
+                                lon_inside && south <= lat && lat <= north
+                            })
+                            .count();
+
+                        let mut results2 = 0;
+                        let _ = index.look_up(&query, |_place| {
+                            results2 += 1;
+                            ControlFlow::Continue(())
+                        });
+
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    fn geo_point(lon: f64, lat: f64) -> GeoPoint {
+        GeoPoint {
+            position: [lon, lat],
+        }
+    }
+
+    #[test]
+    fn geo_point_antipodal() {
+        let half_circumference = PI;
+
+        for (lhs, rhs) in [
+            (geo_point(0.0, 0.0), geo_point(180.0, 0.0)),
+            (geo_point(-90.0, 0.0), geo_point(90.0, 0.0)),
+            (geo_point(0.0, 90.0), geo_point(0.0, -90.0)),
+            (geo_point(45.0, 30.0), geo_point(-135.0, -30.0)),
+        ] {
+            let distance = lhs.distance_2(&rhs).sqrt();
+
+            assert!((distance - half_circumference).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn geo_point_antimeridian() {
+        struct Place(GeoPoint);
+
+        impl Object for Place {
+            type Point = GeoPoint;
+
+            fn position(&self) -> &Self::Point {
+                &self.0
+            }
+        }
+
+        let index = KdTree::new(
+            [
+                geo_point(170.0, 0.0),
+                geo_point(179.9, 0.0),
+                geo_point(-170.0, 0.0),
+                geo_point(0.0, 0.0),
+            ]
+            .map(Place),
+        );
+
+        let nearest = index.nearest(&geo_point(-179.9, 0.0)).unwrap();
+
+        assert_eq!(nearest.0.position, [179.9, 0.0]);
+
+        let distance = geo_point(-179.9, 0.0).distance_2(&nearest.0).sqrt();
+
+        assert!((distance - 0.2_f64.to_radians()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn random_geo_point_nearest() {
+        TestRunner::default()
+            .run(
+                &(
+                    vec((-180.0..=180.0, -90.0..=90.0), 100),
+                    vec((-180.0..=180.0, -90.0..=90.0), 10),
+                ),
+                |(points, targets)| {
+                    let points = points
+                        .into_iter()
+                        .map(|(lon, lat)| geo_point(lon, lat))
+                        .collect();
+
+                    let targets = targets
+                        .into_iter()
+                        .map(|(lon, lat)| geo_point(lon, lat))
+                        .collect::<Vec<_>>();
+
+                    assert_nearest_matches_brute_force(points, &targets);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_great_circle_nearest() {
+        TestRunner::default()
+            .run(
+                &(
+                    vec((-180.0..=180.0, -90.0..=90.0), 100),
+                    vec((-180.0..=180.0, -90.0..=90.0), 10),
+                ),
+                |(places, targets)| {
+                    let index = KdTree::new(
+                        places
+                            .into_iter()
+                            .map(|(lon, lat)| Place([lon, lat]))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    let metric = GreatCircle { radius: 6371.0 };
+
+                    for (lon, lat) in targets {
+                        let target = [lon, lat];
+
+                        let result1 = index
+                            .iter()
+                            .map(|place| metric.distance(&target, &place.0))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                            .unwrap();
+
+                        let result2 = index.nearest_by_metric(&target, &metric).unwrap();
+
+                        assert_eq!(result1, metric.distance(&target, &result2.0));
+
+                        let angle = geo_point(lon, lat)
+                            .distance_2(&geo_point(result2.0[0], result2.0[1]))
+                            .sqrt();
+
+                        assert!((result1.sqrt() - 6371.0 * angle).abs() < 1e-6);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
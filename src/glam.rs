@@ -0,0 +1,102 @@
+use glam::{Vec2, Vec3, Vec3A};
+
+use crate::{Distance, Point};
+
+/// Two-dimensional real space using Euclidean distance
+///
+/// Requires the `glam` feature.
+impl Point for Vec2 {
+    const DIM: usize = 2;
+
+    type Coord = f32;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self.to_array()[axis]
+    }
+}
+
+impl Distance for Vec2 {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        (*self - *other).length_squared()
+    }
+}
+
+/// Three-dimensional real space using Euclidean distance
+///
+/// Requires the `glam` feature.
+impl Point for Vec3 {
+    const DIM: usize = 3;
+
+    type Coord = f32;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self.to_array()[axis]
+    }
+}
+
+impl Distance for Vec3 {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        (*self - *other).length_squared()
+    }
+}
+
+/// Three-dimensional real space using Euclidean distance, with SIMD-aligned storage
+///
+/// Requires the `glam` feature.
+impl Point for Vec3A {
+    const DIM: usize = 3;
+
+    type Coord = f32;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self.to_array()[axis]
+    }
+}
+
+impl Distance for Vec3A {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        (*self - *other).length_squared()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    use crate::tests::assert_nearest_matches_brute_force;
+
+    #[test]
+    fn random_glam_nearest() {
+        TestRunner::default()
+            .run(
+                &(
+                    vec([0.0_f32..=1.0, 0.0_f32..=1.0, 0.0_f32..=1.0], 100),
+                    vec([0.0_f32..=1.0, 0.0_f32..=1.0, 0.0_f32..=1.0], 10),
+                ),
+                |(points, targets)| {
+                    assert_nearest_matches_brute_force(
+                        points.iter().map(|&[x, y, _z]| Vec2::new(x, y)).collect(),
+                        &targets
+                            .iter()
+                            .map(|&[x, y, _z]| Vec2::new(x, y))
+                            .collect::<Vec<_>>(),
+                    );
+
+                    assert_nearest_matches_brute_force(
+                        points.iter().copied().map(Vec3::from).collect(),
+                        &targets.iter().copied().map(Vec3::from).collect::<Vec<_>>(),
+                    );
+
+                    assert_nearest_matches_brute_force(
+                        points.iter().copied().map(Vec3A::from).collect(),
+                        &targets.iter().copied().map(Vec3A::from).collect::<Vec<_>>(),
+                    );
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+use std::ops::ControlFlow;
+
+use num_traits::Float;
+
+use crate::{look_up::look_up_at_axis, split, KdTree, Object, WithinDistance};
+
+impl<O, S, T, const N: usize> KdTree<O, S>
+where
+    O: Object<Point = [T; N]>,
+    T: Float,
+    S: AsRef<[O]>,
+{
+    /// Find all pairs of objects from `self` and `other` which are within the given Euclidean `distance` of each other
+    ///
+    /// Matching pairs are passed to the `visitor` as they are found.
+    /// Depending on its [return value][`ControlFlow`], the search is continued or stopped.
+    ///
+    /// Both trees are traversed simultaneously, always splitting the larger of the two current subtrees.
+    /// The regions covered by the subtrees are tracked as axis-aligned bounding boxes (AABB) bounded by the splitting planes of their ancestors
+    /// and a pair of subtrees is pruned if the Euclidean distance between their AABB is larger than `distance`.
+    /// This is usually much faster than looking up the objects of one tree in the other one by one.
+    pub fn join_within<'a, 'b, O2, S2, V>(
+        &'a self,
+        other: &'b KdTree<O2, S2>,
+        distance: T,
+        visitor: V,
+    ) -> ControlFlow<()>
+    where
+        O2: Object<Point = [T; N]>,
+        S2: AsRef<[O2]>,
+        V: FnMut(&'a O, &'b O2) -> ControlFlow<()>,
+    {
+        let mut args = JoinArgs {
+            distance,
+            distance_2: distance * distance,
+            visitor,
+        };
+
+        join(
+            &mut args,
+            Subtree::new(self.objects.as_ref()),
+            Subtree::new(other.objects.as_ref()),
+        )
+    }
+}
+
+struct JoinArgs<T, V> {
+    distance: T,
+    distance_2: T,
+    visitor: V,
+}
+
+struct Subtree<'a, O, T, const N: usize> {
+    objects: &'a [O],
+    axis: usize,
+    aabb: ([T; N], [T; N]),
+}
+
+impl<'a, O, T, const N: usize> Subtree<'a, O, T, N>
+where
+    O: Object<Point = [T; N]>,
+    T: Float,
+{
+    fn new(objects: &'a [O]) -> Self {
+        Self {
+            objects,
+            axis: 0,
+            aabb: ([T::neg_infinity(); N], [T::infinity(); N]),
+        }
+    }
+
+    fn split(self) -> (Self, &'a O, Self) {
+        let (left, object, right) = split(self.objects);
+
+        let coord = object.position()[self.axis];
+        let next_axis = (self.axis + 1) % N;
+
+        let mut left_aabb = self.aabb;
+        left_aabb.1[self.axis] = coord;
+
+        let mut right_aabb = self.aabb;
+        right_aabb.0[self.axis] = coord;
+
+        let left = Self {
+            objects: left,
+            axis: next_axis,
+            aabb: left_aabb,
+        };
+
+        let right = Self {
+            objects: right,
+            axis: next_axis,
+            aabb: right_aabb,
+        };
+
+        (left, object, right)
+    }
+}
+
+impl<O, T, const N: usize> Clone for Subtree<'_, O, T, N>
+where
+    T: Copy,
+{
+    fn clone(&self) -> Self {
+        Self {
+            objects: self.objects,
+            axis: self.axis,
+            aabb: self.aabb,
+        }
+    }
+}
+
+fn join<'a, 'b, O, O2, T, V, const N: usize>(
+    args: &mut JoinArgs<T, V>,
+    lhs: Subtree<'a, O, T, N>,
+    rhs: Subtree<'b, O2, T, N>,
+) -> ControlFlow<()>
+where
+    O: Object<Point = [T; N]>,
+    O2: Object<Point = [T; N]>,
+    T: Float,
+    V: FnMut(&'a O, &'b O2) -> ControlFlow<()>,
+{
+    if lhs.objects.is_empty() || rhs.objects.is_empty() {
+        return ControlFlow::Continue(());
+    }
+
+    if aabb_distance_2(&lhs.aabb, &rhs.aabb) > args.distance_2 {
+        return ControlFlow::Continue(());
+    }
+
+    if lhs.objects.len() >= rhs.objects.len() {
+        let (left, object, right) = lhs.split();
+
+        let query = WithinDistance::new(*object.position(), args.distance);
+        let visitor = &mut args.visitor;
+
+        look_up_at_axis(rhs.objects, rhs.axis, &query, |other| {
+            visitor(object, other)
+        })?;
+
+        join(args, left, rhs.clone())?;
+        join(args, right, rhs)
+    } else {
+        let (left, other, right) = rhs.split();
+
+        let query = WithinDistance::new(*other.position(), args.distance);
+        let visitor = &mut args.visitor;
+
+        look_up_at_axis(lhs.objects, lhs.axis, &query, |object| {
+            visitor(object, other)
+        })?;
+
+        join(args, lhs.clone(), left)?;
+        join(args, lhs, right)
+    }
+}
+
+fn aabb_distance_2<T, const N: usize>(lhs: &([T; N], [T; N]), rhs: &([T; N], [T; N])) -> T
+where
+    T: Float,
+{
+    (0..N).fold(T::zero(), |res, axis| {
+        let gap = (lhs.0[axis] - rhs.1[axis])
+            .max(rhs.0[axis] - lhs.1[axis])
+            .max(T::zero());
+
+        res + gap * gap
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::{tests::random_objects, Distance};
+
+    #[test]
+    fn random_join_within() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_objects(50), 0.0_f32..=0.2),
+                |(lhs, rhs, distance)| {
+                    let lhs = KdTree::new(lhs);
+                    let rhs = KdTree::new(rhs);
+
+                    let mut results1 = lhs
+                        .iter()
+                        .flat_map(|object| rhs.iter().map(move |other| (object, other)))
+                        .filter(|(object, other)| {
+                            object.0.distance_2(&other.0) <= distance * distance
+                        })
+                        .collect::<Vec<_>>();
+
+                    let mut results2 = Vec::new();
+                    let _ = lhs.join_within(&rhs, distance, |object, other| {
+                        results2.push((object, other));
+                        ControlFlow::Continue(())
+                    });
+
+                    results1.sort_unstable();
+                    results2.sort_unstable();
+                    assert_eq!(results1, results2);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![deny(missing_docs, missing_debug_implementations)]
 
@@ -7,7 +8,7 @@
 //! Its implementation is simple as the objects in the index are fixed after construction.
 //! This also enables a flat and thereby cache-friendly memory layout which can be backed by memory maps.
 //!
-//! The library provides optional integration with [rayon] for parallel construction and queries and [serde] for (de-)serialization of the trees.
+//! The library provides optional integration with [rayon] for parallel construction and queries, [serde] for (de-)serialization of the trees and [rand] for sampling query results.
 //!
 //! # Example
 //!
@@ -59,6 +60,8 @@
 //! assert_eq!(closest, 2);
 //! ```
 //!
+//! Points represented as arrays `[T; N]` implement [`Object`] themselves, positioned at their own coordinates, so that trees of plain points can be built without defining a wrapper type.
+//!
 //! The [`KdTree`] data structure is generic over its backing storage as long as it can be converted into a slice via the [`AsRef`] trait.
 //! This can for instance be used to memory map k-d trees from persistent storage.
 //!
@@ -100,19 +103,126 @@
 //! let index = KdTree::new_unchecked(PointCloud(map));
 //! # Ok(()) }
 //! ```
-
+//!
+//! For objects implementing `bytemuck::Pod`, the optional `bytemuck` feature provides `KdTree::as_bytes` and `KdTree::from_bytes_unchecked` which perform this cast without `unsafe` code, checking size and alignment.
+//!
+//! Buffers which interleave positions with other per-point data at a fixed stride, e.g. GPU vertex buffers, do not need to be repacked:
+//! Describing a whole record as a `#[repr(C)]` type implementing [`Object`] makes the tree read the positions directly from the interleaved buffer.
+//! The same alignment and size preconditions as above apply, i.e. the buffer must be aligned for and its stride must equal the size of that type.
+//! Since the tree is sorted in place, the records are permuted as a whole and their auxiliary data stays attached to their positions.
+//!
+//! # Usage without the standard library
+//!
+//! The `std` feature is enabled by default. Without it, the library is `no_std` and does not depend on `alloc` either,
+//! which also disables the other optional features. The remaining API neither allocates nor requires a global allocator:
+//!
+//! * the [`Point`], [`Distance`] and [`Object`] traits together with their implementations for arrays and tuples,
+//! * [`KdTree`] over borrowed or otherwise user-provided storage, which has no default type parameter in this configuration,
+//! * its in-place construction via [`new`][KdTree::new], `try_new`, `rebuild` and `repair` as well as `new_unchecked`, `get_mut`, `into_storage`, `dim`, `stats` and `bounds`,
+//! * the queries [`look_up`][KdTree::look_up], `look_up_among`, `look_up_moving` and `count`,
+//! * the nearest neighbour searches [`nearest`][KdTree::nearest], `nearest_by_metric`, `nearest_within`, `nearest_matching`, `nearest_excluding_regions`, `nearest_approx`,
+//!   `nearest_approx_verified`, `nearest_hinted`, `nearest_budgeted`, `nearest_progressive`, `nearest_stable`, `nearest_original_index` and `nearest_in_regions`,
+//! * the free functions [`look_up_slice`] and [`nearest_slice`],
+//! * the [`Metric`] trait together with [`Euclidean`] and [`Minkowski`] and
+//! * the [`Query`] trait and all query types except `AtPositions`, which builds a tree internally.
+//!
+//! All other items require the `std` feature.
+
+#[cfg(feature = "std")]
+mod adaptive;
+#[cfg(feature = "std")]
+mod analysis;
+#[cfg(feature = "std")]
+mod at_axis;
+#[cfg(feature = "std")]
+mod bfs;
+#[cfg(feature = "std")]
+mod bucketed;
+#[cfg(feature = "std")]
+mod geo;
+#[cfg(feature = "glam")]
+mod glam;
+#[cfg(feature = "std")]
+mod join;
 mod look_up;
+mod metric;
+#[cfg(feature = "std")]
+mod minkowski;
+#[cfg(feature = "std")]
+mod mixed;
+#[cfg(feature = "std")]
+mod morton;
+#[cfg(feature = "nalgebra")]
+mod nalgebra;
 mod nearest;
+#[cfg(feature = "std")]
+mod owned;
+#[cfg(feature = "bytemuck")]
+mod pod;
+#[cfg(feature = "std")]
+mod project;
+#[cfg(feature = "std")]
+mod quantized;
+#[cfg(feature = "wide")]
+mod simd;
 mod sort;
-
-pub use look_up::{Query, WithinBoundingBox, WithinDistance};
-
-use std::marker::PhantomData;
-use std::ops::Deref;
+#[cfg(feature = "std")]
+mod tracker;
+#[cfg(feature = "std")]
+mod validate;
+
+#[cfg(feature = "std")]
+pub use adaptive::AdaptiveKdTree;
+#[cfg(feature = "std")]
+pub use at_axis::KdTreeAt;
+#[cfg(feature = "std")]
+pub use bfs::BfsKdTree;
+#[cfg(feature = "std")]
+pub use bucketed::{BucketedKdTree, KdTreeBuilder};
+#[cfg(feature = "std")]
+pub use geo::{GeoBoundingBox, GeoPoint, GreatCircle};
+#[cfg(feature = "std")]
+pub use look_up::AtPositions;
+pub use look_up::{
+    look_up_slice, And, Not, OnSideOfPlane, Or, Query, WithinBoundingBox, WithinChebyshevDistance,
+    WithinDistance, WithinDistanceSubspace, WithinDistanceTracked, WithinEllipsoid,
+    WithinManhattanDistance, WithinOrientedBox,
+};
+pub use metric::{Euclidean, Metric, Minkowski};
+#[cfg(feature = "std")]
+pub use minkowski::MinkowskiDistance;
+#[cfg(feature = "std")]
+pub use mixed::MixedPoint;
+pub use nearest::nearest_slice;
+#[cfg(feature = "std")]
+pub use owned::ArcRef;
+#[cfg(feature = "bytemuck")]
+pub use pod::FormatError;
+#[cfg(feature = "std")]
+pub use project::{project, ProjectedObject};
+#[cfg(feature = "std")]
+pub use quantized::QuantizedPoint;
+#[cfg(feature = "wide")]
+pub use simd::SimdPoint;
+#[cfg(feature = "std")]
+pub use sort::BuildStats;
+pub use sort::NonComparableCoord;
+#[cfg(feature = "std")]
+pub use tracker::{ProximityDelta, ProximityTracker};
+#[cfg(feature = "rayon")]
+pub use validate::validate_many;
+#[cfg(feature = "std")]
+pub use validate::InvalidTree;
+
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::vec;
 
 use num_traits::Num;
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 /// Defines a [finite-dimensional][Self::DIM] space in terms of [coordinate values][Self::coord] along a chosen set of axes
 pub trait Point {
@@ -132,6 +242,16 @@ pub trait Distance: Point {
     ///
     /// This is called during nearest neighbour search and hence only the relation between two distance values is required so that computing square roots can be avoided.
     fn distance_2(&self, other: &Self) -> Self::Coord;
+
+    /// Return a lower bound of the squared distance between `self` and any point whose coordinate value along the given `axis` is `coord` or beyond
+    ///
+    /// This is called during nearest neighbour search to prune subtrees and hence must be consistent with [`distance_2`][Self::distance_2].
+    /// The default implementation returns the squared difference of the coordinate values which is correct for Euclidean distance.
+    fn axis_distance_2(&self, axis: usize, coord: Self::Coord) -> Self::Coord {
+        let diff = self.coord(axis) - coord;
+
+        diff * diff
+    }
 }
 
 /// `N`-dimensional space using [Euclidean distance](https://en.wikipedia.org/wiki/Euclidean_distance)
@@ -161,7 +281,86 @@ where
     }
 }
 
+/// Two-dimensional space using Euclidean distance, mapping the axes to the fields of the tuple
+impl<T> Point for (T, T)
+where
+    T: Num + Copy + PartialOrd,
+{
+    const DIM: usize = 2;
+
+    type Coord = T;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        [self.0, self.1][axis]
+    }
+}
+
+impl<T> Distance for (T, T)
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        [self.0, self.1].distance_2(&[other.0, other.1])
+    }
+}
+
+/// Three-dimensional space using Euclidean distance, mapping the axes to the fields of the tuple
+impl<T> Point for (T, T, T)
+where
+    T: Num + Copy + PartialOrd,
+{
+    const DIM: usize = 3;
+
+    type Coord = T;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        [self.0, self.1, self.2][axis]
+    }
+}
+
+impl<T> Distance for (T, T, T)
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        [self.0, self.1, self.2].distance_2(&[other.0, other.1, other.2])
+    }
+}
+
 /// Defines the objects which can be organized in a [`KdTree`] by positioning them in the vector space defined via the [`Point`] trait
+///
+/// Since [`position`][Self::position] returns a reference, objects cannot look up their position in a separate pool of coordinates which is passed in as context.
+/// Instead, such objects can borrow the pool themselves at the cost of an additional pointer per object:
+///
+/// ```
+/// use sif_kdtree::{KdTree, Object};
+///
+/// struct Entity<'a> {
+///     index: usize,
+///     positions: &'a [[f64; 2]],
+/// }
+///
+/// impl Object for Entity<'_> {
+///     type Point = [f64; 2];
+///
+///     fn position(&self) -> &Self::Point {
+///         &self.positions[self.index]
+///     }
+/// }
+///
+/// let positions = [[0.0, 0.0], [1.0, 1.0], [2.0, 0.5]];
+///
+/// let index = KdTree::new(
+///     (0..positions.len())
+///         .map(|index| Entity {
+///             index,
+///             positions: &positions,
+///         })
+///         .collect::<Box<[_]>>(),
+/// );
+///
+/// assert_eq!(index.nearest(&[1.5, 1.0]).unwrap().index, 1);
+/// ```
 pub trait Object {
     /// The [`Point`] implementation used to represent the [position][`Self::position`] of these objects
     type Point: Point;
@@ -172,6 +371,36 @@ pub trait Object {
     fn position(&self) -> &Self::Point;
 }
 
+/// Statistics on the shape of a [`KdTree`] as returned by [`KdTree::stats`]
+///
+/// Depths are counted in levels, i.e. the root is at depth one, and leaves are nodes without children.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TreeStats {
+    /// The number of objects
+    pub len: usize,
+    /// The number of levels of the tree, i.e. `⌊log2(n)⌋ + 1` for `n` objects
+    pub height: usize,
+    /// The depth of the shallowest leaf
+    pub min_leaf_depth: usize,
+    /// The depth of the deepest leaf, which is the same as the height
+    pub max_leaf_depth: usize,
+}
+
+/// Points are trivially objects positioned at themselves
+///
+/// This is useful to build trees without any payload, e.g. when deserializing the output of [`KdTree::serialize_positions`].
+impl<T, const N: usize> Object for [T; N]
+where
+    T: Num + Copy + PartialOrd,
+{
+    type Point = Self;
+
+    fn position(&self) -> &Self::Point {
+        self
+    }
+}
+
+#[cfg(feature = "std")]
 /// An immutable, flat representation of a [k-d tree](https://en.wikipedia.org/wiki/K-d_tree)
 ///
 /// Accelerates spatial queries and nearest neighbour search by sorting the objects according to the coordinate values of their positions.
@@ -189,6 +418,19 @@ where
     _marker: PhantomData<O>,
 }
 
+#[cfg(not(feature = "std"))]
+/// An immutable, flat representation of a [k-d tree](https://en.wikipedia.org/wiki/K-d_tree)
+///
+/// Without the `std` feature, there is no default for the backing storage which is usually a borrowed slice `&[O]`.
+#[derive(Debug, Default, Clone)]
+pub struct KdTree<O, S>
+where
+    S: AsRef<[O]>,
+{
+    objects: S,
+    _marker: PhantomData<O>,
+}
+
 impl<O, S> KdTree<O, S>
 where
     O: Object,
@@ -197,12 +439,121 @@ where
     /// Interprets the given `objects` as a tree
     ///
     /// Supplying `objects` which are not actually sorted as a k-d tree is safe but will lead to incorrect results.
+    ///
+    /// Since the structure of the tree is implied by the number of objects alone, traversals of the tree never recurse deeper than `⌈log2(n + 1)⌉` levels,
+    /// independently of whether the `objects` are sorted correctly. Hence no limit on the recursion depth is necessary when interpreting untrusted data.
     pub fn new_unchecked(objects: S) -> Self {
         Self {
             objects,
             _marker: PhantomData,
         }
     }
+
+    /// Return the dimension of the space in which the objects of this tree are positioned
+    ///
+    /// This is the same as [`Point::DIM`] of the [object's positions][Object::Point] and is available in constant contexts, e.g. to assert that two trees have the same dimension at compile time.
+    pub const fn dim() -> usize {
+        O::Point::DIM
+    }
+
+    /// Return statistics on the shape of this tree
+    ///
+    /// The shape is implied by the number of objects alone, so this does not access the objects and takes `O(log n)` time.
+    pub fn stats(&self) -> TreeStats {
+        let len = self.objects.as_ref().len();
+
+        if len == 0 {
+            return TreeStats::default();
+        }
+
+        let height = (usize::BITS - len.leading_zeros()) as usize;
+
+        // The right subtree is never larger than the left one, so the shallowest leaf is found by preferring it.
+        let mut min_leaf_depth = 1;
+        let mut subtree = len;
+
+        while subtree > 1 {
+            let left = subtree / 2;
+            let right = subtree - left - 1;
+
+            subtree = if right != 0 { right } else { left };
+            min_leaf_depth += 1;
+        }
+
+        TreeStats {
+            len,
+            height,
+            min_leaf_depth,
+            max_leaf_depth: height,
+        }
+    }
+
+    /// Return the backing storage of this tree
+    ///
+    /// Together with [`new`][Self::new], this allows reusing the storage for multiple trees as the construction sorts the objects in place without allocating.
+    pub fn into_storage(self) -> S {
+        self.objects
+    }
+
+    #[cfg(feature = "serde")]
+    /// Serialize only the positions of the objects in this tree
+    ///
+    /// The positions are written as a sequence in the order of the tree, i.e. the payloads of the objects are dropped.
+    /// Since [points are objects themselves][Object], the result can be deserialized as a tree of type `KdTree<O::Point>` without rebuilding it,
+    /// for example to reconstruct the spatial structure when the payloads are not required.
+    ///
+    /// Requires the `serde` feature.
+    pub fn serialize_positions<Ser>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error>
+    where
+        O::Point: Serialize,
+        Ser: Serializer,
+    {
+        serializer.collect_seq(self.objects.as_ref().iter().map(|object| object.position()))
+    }
+
+    #[cfg(feature = "std")]
+    /// Return the objects of this tree as a vector
+    ///
+    /// The objects are in the order of the tree, not in the order in which they were passed to the constructor.
+    pub fn into_vec(self) -> Vec<O>
+    where
+        S: Into<Vec<O>>,
+    {
+        self.objects.into()
+    }
+}
+
+impl<O, S, T, const N: usize> KdTree<O, S>
+where
+    O: Object<Point = [T; N]>,
+    T: Num + Copy + PartialOrd,
+    S: AsRef<[O]>,
+{
+    /// Compute the tight axis-aligned bounding box (AABB) of the positions of all objects in this tree
+    ///
+    /// The AABB is represented like [`Query::aabb`], i.e. by the corners with first the smallest and then the largest coordinate values,
+    /// so it can for example be used to clamp unbounded queries like [`OnSideOfPlane`] to the extent of the tree.
+    ///
+    /// Returns `None` if the tree is empty.
+    pub fn bounds(&self) -> Option<([T; N], [T; N])> {
+        let (first, rest) = self.objects.as_ref().split_first()?;
+
+        let mut bounds = (*first.position(), *first.position());
+
+        for object in rest {
+            for (axis, coord) in object.position().iter().enumerate() {
+                if bounds.0[axis] > *coord {
+                    bounds.0[axis] = *coord;
+                }
+
+                if bounds.1[axis] < *coord {
+                    bounds.1[axis] = *coord;
+                }
+            }
+        }
+
+        Some(bounds)
+    }
 }
 
 impl<O, S> Deref for KdTree<O, S>
@@ -225,6 +576,22 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+/// Consumes the tree yielding its objects in the order of the tree, not in the order in which they were passed to the constructor
+///
+/// This is implemented via [`into_vec`][KdTree::into_vec] and hence available for all storage types convertible into a [`Vec`] like the default `Box<[O]>`.
+impl<O, S> IntoIterator for KdTree<O, S>
+where
+    S: AsRef<[O]> + Into<Vec<O>>,
+{
+    type Item = O;
+    type IntoIter = vec::IntoIter<O>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.objects.into().into_iter()
+    }
+}
+
 fn split<O>(objects: &[O]) -> (&[O], &O, &[O]) {
     let (left, objects) = objects.split_at(objects.len() / 2);
     let (mid, right) = objects.split_first().unwrap();
@@ -232,6 +599,28 @@ fn split<O>(objects: &[O]) -> (&[O], &O, &[O]) {
     (left, mid, right)
 }
 
+/// Checks whether the root of the non-empty subtree `objects` separates its left and right subtrees along `axis`
+fn separates<O>(objects: &[O], axis: usize) -> bool
+where
+    O: Object,
+{
+    let (left, object, right) = split(objects);
+
+    let coord = object.position().coord(axis);
+
+    left.iter()
+        .all(|object| object.position().coord(axis) <= coord)
+        && right
+            .iter()
+            .all(|object| coord <= object.position().coord(axis))
+}
+
+fn index_of<O>(objects: &[O], object: &O) -> usize {
+    let offset = object as *const O as usize - objects.as_ptr() as usize;
+
+    offset.checked_div(size_of::<O>()).unwrap_or(0)
+}
+
 fn contains<P>(aabb: &(P, P), position: &P) -> bool
 where
     P: Point,
@@ -241,38 +630,45 @@ where
     })
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
 
     use std::cmp::Ordering;
+    use std::fmt::Debug;
+    use std::ops::ControlFlow;
 
-    use proptest::{collection::vec, strategy::Strategy};
+    use num_traits::Float;
+    use proptest::{collection::vec, strategy::Strategy, test_runner::TestRunner};
 
     pub fn random_points(len: usize) -> impl Strategy<Value = Vec<[f32; 2]>> {
         (vec(0.0_f32..=1.0, len), vec(0.0_f32..=1.0, len))
             .prop_map(|(x, y)| x.into_iter().zip(y).map(|(x, y)| [x, y]).collect())
     }
 
-    #[derive(Debug, PartialEq)]
-    pub struct RandomObject(pub [f32; 2]);
+    pub fn random_points_1d(len: usize) -> impl Strategy<Value = Vec<[f32; 1]>> {
+        vec(0.0_f32..=1.0, len).prop_map(|x| x.into_iter().map(|x| [x]).collect())
+    }
 
-    impl Eq for RandomObject {}
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct RandomObject<const N: usize = 2>(pub [f32; N]);
 
-    impl PartialOrd for RandomObject {
+    impl<const N: usize> Eq for RandomObject<N> {}
+
+    impl<const N: usize> PartialOrd for RandomObject<N> {
         fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
             Some(self.cmp(other))
         }
     }
 
-    impl Ord for RandomObject {
+    impl<const N: usize> Ord for RandomObject<N> {
         fn cmp(&self, other: &Self) -> Ordering {
             self.0.partial_cmp(&other.0).unwrap()
         }
     }
 
-    impl Object for RandomObject {
-        type Point = [f32; 2];
+    impl<const N: usize> Object for RandomObject<N> {
+        type Point = [f32; N];
 
         fn position(&self) -> &Self::Point {
             &self.0
@@ -282,4 +678,248 @@ mod tests {
     pub fn random_objects(len: usize) -> impl Strategy<Value = Box<[RandomObject]>> {
         random_points(len).prop_map(|points| points.into_iter().map(RandomObject).collect())
     }
+
+    pub fn random_objects_1d(len: usize) -> impl Strategy<Value = Box<[RandomObject<1>]>> {
+        random_points_1d(len).prop_map(|points| points.into_iter().map(RandomObject).collect())
+    }
+
+    pub fn random_queries(len: usize) -> impl Strategy<Value = Vec<WithinDistance<f32, 2>>> {
+        (random_points(len), vec(0.0_f32..=1.0, len)).prop_map(|(centers, distances)| {
+            centers
+                .into_iter()
+                .zip(distances)
+                .map(|(center, distance)| WithinDistance::new(center, distance))
+                .collect()
+        })
+    }
+
+    struct PointObject<P>(P);
+
+    impl<P> Object for PointObject<P>
+    where
+        P: Point,
+    {
+        type Point = P;
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    /// Check that [`KdTree::nearest`] finds an object at the minimal distance to each of the `targets`
+    ///
+    /// This requires [`Distance::axis_distance_2`] to be an admissible bound, i.e. to never exceed [`Distance::distance_2`]
+    /// to any point on the far side of the splitting plane, which is also checked directly for the given `points`.
+    pub fn assert_nearest_matches_brute_force<P>(points: Vec<P>, targets: &[P])
+    where
+        P: Distance,
+        P::Coord: Float + Debug,
+    {
+        for target in targets {
+            for point in &points {
+                let distance_2 = target.distance_2(point);
+
+                for axis in 0..P::DIM {
+                    assert!(target.axis_distance_2(axis, point.coord(axis)) <= distance_2);
+                }
+            }
+        }
+
+        let index = KdTree::new(points.into_iter().map(PointObject).collect::<Box<[_]>>());
+
+        for target in targets {
+            let result1 = index
+                .iter()
+                .map(|object| target.distance_2(&object.0))
+                .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+            let result2 = index
+                .nearest(target)
+                .map(|object| target.distance_2(&object.0));
+
+            assert_eq!(result1, result2);
+        }
+    }
+
+    /// Checks that `look_up` visits exactly the `objects` matching the given `query`, in any order
+    ///
+    /// This is shared by the tests of the different tree types which are passed in via their `look_up` method.
+    pub fn assert_look_up_matches_brute_force<'a, Q, L, const N: usize>(
+        objects: &'a [RandomObject<N>],
+        query: &Q,
+        look_up: L,
+    ) where
+        Q: Query<[f32; N]>,
+        L: FnOnce(&mut dyn FnMut(&'a RandomObject<N>) -> ControlFlow<()>) -> ControlFlow<()>,
+    {
+        let mut results1 = objects
+            .iter()
+            .filter(|object| query.test(object.position()))
+            .collect::<Vec<_>>();
+
+        let mut results2 = Vec::new();
+        let _ = look_up(&mut |object| {
+            results2.push(object);
+            ControlFlow::Continue(())
+        });
+
+        results1.sort_unstable();
+        results2.sort_unstable();
+        assert_eq!(results1, results2);
+    }
+
+    /// Checks that the `result` of a nearest neighbour search is as close to the given `target` as the nearest of all `objects`
+    ///
+    /// Only the distances are compared as ties between objects at the same distance may be broken differently.
+    pub fn assert_nearest_matches_objects<const N: usize>(
+        objects: &[RandomObject<N>],
+        target: &[f32; N],
+        result: Option<&RandomObject<N>>,
+    ) {
+        let result1 = objects
+            .iter()
+            .map(|object| object.0.distance_2(target))
+            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+        let result2 = result.map(|object| object.0.distance_2(target));
+
+        assert_eq!(result1, result2);
+    }
+
+    #[test]
+    fn random_euclidean_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_points(10)),
+                |(points, targets)| {
+                    assert_nearest_matches_brute_force(points, &targets);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn random_serialize_positions() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index = KdTree::new(objects);
+
+                let mut buf = Vec::new();
+                index
+                    .serialize_positions(&mut serde_json::Serializer::new(&mut buf))
+                    .unwrap();
+
+                let positions = serde_json::from_slice::<KdTree<[f32; 2]>>(&buf).unwrap();
+
+                assert!(index
+                    .iter()
+                    .map(|object| object.position())
+                    .eq(positions.iter()));
+                assert!(positions.is_valid());
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    fn leaf_depths(len: usize) -> (usize, usize) {
+        let left = len / 2;
+        let right = len - left - 1;
+
+        let depths = IntoIterator::into_iter([left, right])
+            .filter(|len| *len != 0)
+            .map(leaf_depths)
+            .reduce(|lhs, rhs| (lhs.0.min(rhs.0), lhs.1.max(rhs.1)))
+            .unwrap_or((0, 0));
+
+        (depths.0 + 1, depths.1 + 1)
+    }
+
+    #[test]
+    fn stats_match_shape() {
+        for len in 0..300 {
+            let index = KdTree::new((0..len).map(|x| [x as f32]).collect::<Box<[_]>>());
+
+            let stats = index.stats();
+
+            assert_eq!(stats.len, len);
+
+            if len == 0 {
+                assert_eq!(stats, TreeStats::default());
+            } else {
+                let (min_leaf_depth, max_leaf_depth) = leaf_depths(len);
+
+                assert_eq!(stats.height, (len as f64).log2().floor() as usize + 1);
+                assert_eq!(stats.min_leaf_depth, min_leaf_depth);
+                assert_eq!(stats.max_leaf_depth, max_leaf_depth);
+            }
+        }
+    }
+
+    #[test]
+    fn random_bounds() {
+        TestRunner::default()
+            .run(&(0_usize..100).prop_flat_map(random_objects), |objects| {
+                let index = KdTree::new(objects);
+
+                match index.bounds() {
+                    Some(bounds) => {
+                        for axis in 0..2 {
+                            let coords = || index.iter().map(|object| object.0[axis]);
+
+                            assert_eq!(bounds.0[axis], coords().fold(f32::INFINITY, f32::min));
+                            assert_eq!(bounds.1[axis], coords().fold(f32::NEG_INFINITY, f32::max));
+                        }
+                    }
+                    None => assert!(index.is_empty()),
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_tuple_nearest() {
+        TestRunner::default()
+            .run(
+                &(
+                    vec((0.0_f32..=1.0, 0.0_f32..=1.0), 100),
+                    vec((0.0_f32..=1.0, 0.0_f32..=1.0), 10),
+                ),
+                |(points, targets)| {
+                    assert_nearest_matches_brute_force(points, &targets);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        TestRunner::default()
+            .run(
+                &(
+                    vec((0.0_f32..=1.0, 0.0_f32..=1.0, 0.0_f32..=1.0), 100),
+                    vec((0.0_f32..=1.0, 0.0_f32..=1.0, 0.0_f32..=1.0), 10),
+                ),
+                |(points, targets)| {
+                    assert_nearest_matches_brute_force(points, &targets);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    pub fn random_queries_1d(len: usize) -> impl Strategy<Value = Vec<WithinDistance<f32, 1>>> {
+        (random_points_1d(len), vec(0.0_f32..=1.0, len)).prop_map(|(centers, distances)| {
+            centers
+                .into_iter()
+                .zip(distances)
+                .map(|(center, distance)| WithinDistance::new(center, distance))
+                .collect()
+        })
+    }
 }
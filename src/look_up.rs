@@ -1,10 +1,20 @@
-use std::ops::ControlFlow;
+use core::cell::Cell;
+use core::fmt;
+use core::ops::ControlFlow;
+#[cfg(feature = "rayon")]
+use core::slice;
+#[cfg(feature = "rayon")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use num_traits::{Bounded, Float, Num};
 
-use num_traits::Num;
 #[cfg(feature = "rayon")]
-use rayon::join;
+use rayon::{
+    iter::{self, ParallelIterator},
+    join,
+};
 
-use crate::{contains, split, Distance, KdTree, Object, Point};
+use crate::{contains, index_of, split, Distance, KdTree, Object, Point};
 
 /// Defines a spatial query by its axis-aligned bounding box (AABB) and a method to test a single point
 ///
@@ -24,10 +34,26 @@ pub trait Query<P: Point> {
     fn test(&self, position: &P) -> bool;
 }
 
+/// Queries can be borrowed, e.g. to combine them using [`And`], [`Or`] or [`Not`] without giving them up
+impl<P, Q> Query<P> for &Q
+where
+    P: Point,
+    Q: Query<P> + ?Sized,
+{
+    fn aabb(&self) -> &(P, P) {
+        (**self).aabb()
+    }
+
+    fn test(&self, position: &P) -> bool {
+        (**self).test(position)
+    }
+}
+
 /// A query which yields all objects within a given axis-aligned boundary box (AABB) in `N`-dimensional space
 #[derive(Debug)]
 pub struct WithinBoundingBox<T, const N: usize> {
     aabb: ([T; N], [T; N]),
+    exact: Option<([T; N], [T; N])>,
 }
 
 impl<T, const N: usize> WithinBoundingBox<T, N> {
@@ -35,6 +61,26 @@ impl<T, const N: usize> WithinBoundingBox<T, N> {
     pub fn new(lower: [T; N], upper: [T; N]) -> Self {
         Self {
             aabb: (lower, upper),
+            exact: None,
+        }
+    }
+}
+
+impl<T, const N: usize> WithinBoundingBox<T, N>
+where
+    T: Num + Copy,
+{
+    /// Construct a query as with [`new`][Self::new] but inflate the AABB used to prune the search by `epsilon`
+    ///
+    /// This makes the search robust against rounding errors in the computation of the bounds at the cost of visiting slightly more objects.
+    /// Note that the [test][Query::test] still checks the exact bounds and hence decides which objects match.
+    pub fn with_epsilon(lower: [T; N], upper: [T; N], epsilon: T) -> Self {
+        Self {
+            aabb: (
+                lower.map(|coord| coord - epsilon),
+                upper.map(|coord| coord + epsilon),
+            ),
+            exact: Some((lower, upper)),
         }
     }
 }
@@ -47,8 +93,10 @@ where
         &self.aabb
     }
 
-    fn test(&self, _position: &[T; N]) -> bool {
-        true
+    fn test(&self, position: &[T; N]) -> bool {
+        self.exact
+            .as_ref()
+            .map_or(true, |exact| contains(exact, position))
     }
 }
 
@@ -90,205 +138,2205 @@ where
     }
 }
 
-impl<O, S> KdTree<O, S>
+/// A query which yields all objects within a given [Manhattan distance](https://en.wikipedia.org/wiki/Taxicab_geometry) to a central point in `N`-dimensional real space
+#[derive(Debug)]
+pub struct WithinManhattanDistance<T, const N: usize> {
+    aabb: ([T; N], [T; N]),
+    center: [T; N],
+    distance: T,
+}
+
+impl<T, const N: usize> WithinManhattanDistance<T, N>
 where
-    O: Object,
-    S: AsRef<[O]>,
+    T: Num + Copy + PartialOrd,
 {
-    /// Find objects matching the given `query`
-    ///
-    /// Queries are defined by passing an implementor of the [`Query`] trait.
-    ///
-    /// Objects matching the `query` are passed to the `visitor` as they are found.
-    /// Depending on its [return value][`ControlFlow`], the search is continued or stopped.
-    pub fn look_up<'a, Q, V>(&'a self, query: &Q, visitor: V) -> ControlFlow<()>
-    where
-        Q: Query<O::Point>,
-        V: FnMut(&'a O) -> ControlFlow<()>,
-    {
-        let objects = self.objects.as_ref();
-
-        if !objects.is_empty() {
-            look_up(&mut LookUpArgs { query, visitor }, objects, 0)?;
+    /// Construct a query from the `center` and the largest allowed Manhattan `distance` to it
+    pub fn new(center: [T; N], distance: T) -> Self {
+        Self {
+            aabb: (
+                center.map(|coord| coord - distance),
+                center.map(|coord| coord + distance),
+            ),
+            center,
+            distance,
         }
+    }
+}
 
-        ControlFlow::Continue(())
+impl<T, const N: usize> Query<[T; N]> for WithinManhattanDistance<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.aabb
     }
 
-    #[cfg(feature = "rayon")]
-    /// Find objects matching the given `query`, in parallel
-    ///
-    /// Queries are defined by passing an implementor of the [`Query`] trait.
-    ///
-    /// Objects matching the `query` are passed to the `visitor` as they are found.
-    /// In contrast to the [serial version][Self::look_up], parts of the search can continue
-    /// even after it has been stopped.
-    ///
-    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
-    pub fn par_look_up<'a, Q, V>(&'a self, query: &Q, visitor: V) -> ControlFlow<()>
-    where
-        O: Send + Sync,
-        O::Point: Sync,
-        Q: Query<O::Point> + Sync,
-        V: Fn(&'a O) -> ControlFlow<()> + Sync,
-    {
-        let objects = self.objects.as_ref();
+    fn test(&self, position: &[T; N]) -> bool {
+        let distance = (0..N).fold(T::zero(), |res, axis| {
+            res + abs_diff(self.center[axis], position[axis])
+        });
 
-        if !objects.is_empty() {
-            par_look_up(&LookUpArgs { query, visitor }, objects, 0)?;
-        }
+        distance <= self.distance
+    }
+}
 
-        ControlFlow::Continue(())
+/// Computes the absolute difference using only the operations provided by [`Num`] and [`PartialOrd`]
+fn abs_diff<T>(lhs: T, rhs: T) -> T
+where
+    T: Num + PartialOrd,
+{
+    if lhs < rhs {
+        rhs - lhs
+    } else {
+        lhs - rhs
     }
 }
 
-struct LookUpArgs<'a, Q, V> {
-    query: &'a Q,
-    visitor: V,
+/// A query which yields all objects within a given [Chebyshev distance](https://en.wikipedia.org/wiki/Chebyshev_distance) to a central point in `N`-dimensional real space
+///
+/// The objects within Chebyshev distance `distance` are exactly those inside the axis-aligned box spanning `center ± distance` on every axis.
+/// Hence the [AABB][Query::aabb] is already exact and the [test][Query::test] always succeeds, making this query as cheap as [`WithinBoundingBox`].
+#[derive(Debug)]
+pub struct WithinChebyshevDistance<T, const N: usize> {
+    aabb: ([T; N], [T; N]),
 }
 
-fn look_up<'a, O, Q, V>(
-    args: &mut LookUpArgs<Q, V>,
-    mut objects: &'a [O],
-    mut axis: usize,
-) -> ControlFlow<()>
+impl<T, const N: usize> WithinChebyshevDistance<T, N>
 where
-    O: Object,
-    Q: Query<O::Point>,
-    V: FnMut(&'a O) -> ControlFlow<()>,
+    T: Num + Copy + PartialOrd,
 {
-    loop {
-        let (left, object, right) = split(objects);
-
-        let position = object.position();
-
-        if contains(args.query.aabb(), position) && args.query.test(position) {
-            (args.visitor)(object)?;
+    /// Construct a query from the `center` and the largest allowed Chebyshev `distance` to it
+    pub fn new(center: [T; N], distance: T) -> Self {
+        Self {
+            aabb: (
+                center.map(|coord| coord - distance),
+                center.map(|coord| coord + distance),
+            ),
         }
+    }
+}
 
-        let search_left =
-            !left.is_empty() && args.query.aabb().0.coord(axis) <= position.coord(axis);
-
-        let search_right =
-            !right.is_empty() && position.coord(axis) <= args.query.aabb().1.coord(axis);
+impl<T, const N: usize> Query<[T; N]> for WithinChebyshevDistance<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.aabb
+    }
 
-        axis = (axis + 1) % O::Point::DIM;
+    fn test(&self, _position: &[T; N]) -> bool {
+        true
+    }
+}
 
-        match (search_left, search_right) {
-            (true, true) => {
-                look_up(args, left, axis)?;
+/// A query like [`WithinDistance`] which additionally records the squared distance computed by its most recent [test][Query::test]
+///
+/// During [`look_up`][KdTree::look_up], the test of an object is directly followed by passing it to the visitor if it matches,
+/// so that the visitor can access the squared distance of the current object via [`last_distance_2`][Self::last_distance_2] instead of recomputing it.
+///
+/// Note that recording the distance uses interior mutability which makes this query `!Sync`, so it cannot be used with parallel look-ups.
+pub struct WithinDistanceTracked<T, const N: usize> {
+    query: WithinDistance<T, N>,
+    last_distance_2: Cell<T>,
+}
 
-                objects = right;
-            }
-            (true, false) => objects = left,
-            (false, true) => objects = right,
-            (false, false) => return ControlFlow::Continue(()),
+impl<T, const N: usize> WithinDistanceTracked<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    /// Construct a query from the `center` and the largest allowed Euclidean `distance` to it
+    pub fn new(center: [T; N], distance: T) -> Self {
+        Self {
+            query: WithinDistance::new(center, distance),
+            last_distance_2: Cell::new(T::zero()),
         }
     }
+
+    /// Return the squared distance to the `center` computed by the most recent test, or zero if no position was tested yet
+    pub fn last_distance_2(&self) -> T {
+        self.last_distance_2.get()
+    }
 }
 
-#[cfg(feature = "rayon")]
-fn par_look_up<'a, O, Q, V>(
-    args: &LookUpArgs<Q, V>,
-    mut objects: &'a [O],
-    mut axis: usize,
-) -> ControlFlow<()>
+impl<T, const N: usize> fmt::Debug for WithinDistanceTracked<T, N>
 where
-    O: Object + Send + Sync,
-    O::Point: Sync,
-    Q: Query<O::Point> + Sync,
-    V: Fn(&'a O) -> ControlFlow<()> + Sync,
+    T: fmt::Debug + Copy,
 {
-    loop {
-        let (left, object, right) = split(objects);
-
-        let position = object.position();
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("WithinDistanceTracked")
+            .field("query", &self.query)
+            .field("last_distance_2", &self.last_distance_2)
+            .finish()
+    }
+}
 
-        if contains(args.query.aabb(), position) && args.query.test(position) {
-            (args.visitor)(object)?;
-        }
+impl<T, const N: usize> Query<[T; N]> for WithinDistanceTracked<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.query.aabb
+    }
 
-        let search_left =
-            !left.is_empty() && args.query.aabb().0.coord(axis) <= position.coord(axis);
+    fn test(&self, position: &[T; N]) -> bool {
+        let distance_2 = self.query.center.distance_2(position);
 
-        let search_right =
-            !right.is_empty() && position.coord(axis) <= args.query.aabb().1.coord(axis);
+        self.last_distance_2.set(distance_2);
 
-        axis = (axis + 1) % O::Point::DIM;
+        distance_2 <= self.query.distance_2
+    }
+}
 
-        match (search_left, search_right) {
-            (true, true) => {
-                let (left, right) = join(
-                    || par_look_up(args, left, axis),
-                    || par_look_up(args, right, axis),
-                );
+/// A query which yields all objects within a given distance to a central point measured only along a subset of the axes of `N`-dimensional real space
+///
+/// The AABB of the query is unbounded along the excluded axes using the [smallest and largest][Bounded] coordinate values,
+/// e.g. to find spatially close objects at any time when time is one of the axes.
+#[derive(Debug)]
+pub struct WithinDistanceSubspace<T, const N: usize> {
+    aabb: ([T; N], [T; N]),
+    center: [T; N],
+    mask: [bool; N],
+    distance_2: T,
+}
 
-                left?;
-                right?;
+impl<T, const N: usize> WithinDistanceSubspace<T, N>
+where
+    T: Num + Bounded + Copy + PartialOrd,
+{
+    /// Construct a query from the `center`, the largest allowed Euclidean `distance` to it and the `mask` of the axes included in the distance
+    pub fn new(center: [T; N], distance: T, mask: [bool; N]) -> Self {
+        let mut aabb = ([T::min_value(); N], [T::max_value(); N]);
 
-                return ControlFlow::Continue(());
+        for (axis, included) in mask.iter().enumerate() {
+            if *included {
+                aabb.0[axis] = center[axis] - distance;
+                aabb.1[axis] = center[axis] + distance;
             }
-            (true, false) => objects = left,
-            (false, true) => objects = right,
-            (false, false) => return ControlFlow::Continue(()),
+        }
+
+        Self {
+            aabb,
+            center,
+            mask,
+            distance_2: distance * distance,
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[cfg(feature = "rayon")]
-    use std::sync::Mutex;
+impl<T, const N: usize> Query<[T; N]> for WithinDistanceSubspace<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.aabb
+    }
 
-    use proptest::{collection::vec, strategy::Strategy, test_runner::TestRunner};
+    fn test(&self, position: &[T; N]) -> bool {
+        let distance_2 = (0..N)
+            .filter(|axis| self.mask[*axis])
+            .fold(T::zero(), |res, axis| {
+                let diff = self.center[axis] - position[axis];
 
-    use crate::tests::{random_objects, random_points};
+                res + diff * diff
+            });
 
-    pub fn random_queries(len: usize) -> impl Strategy<Value = Vec<WithinDistance<f32, 2>>> {
-        (random_points(len), vec(0.0_f32..=1.0, len)).prop_map(|(centers, distances)| {
-            centers
-                .into_iter()
-                .zip(distances)
-                .map(|(center, distance)| WithinDistance::new(center, distance))
-                .collect()
-        })
+        distance_2 <= self.distance_2
     }
+}
 
-    #[test]
-    fn random_look_up() {
-        TestRunner::default()
-            .run(
-                &(random_objects(100), random_queries(10)),
-                |(objects, queries)| {
-                    let index = KdTree::new(objects);
+/// A query which yields all objects within a rotated rectangle, i.e. an oriented bounding box, in two-dimensional real space
+#[derive(Debug)]
+pub struct WithinOrientedBox<T> {
+    aabb: ([T; 2], [T; 2]),
+    center: [T; 2],
+    half_extents: [T; 2],
+    cos: T,
+    sin: T,
+}
 
-                    for query in queries {
-                        let mut results1 = index
-                            .iter()
-                            .filter(|object| query.test(object.position()))
-                            .collect::<Vec<_>>();
+impl<T> WithinOrientedBox<T>
+where
+    T: Float,
+{
+    /// Construct a query from the `center`, the `half_extents` along the rectangle's axes and the `angle` in radians by which it is rotated counter-clockwise
+    ///
+    /// The AABB of the query is the one of the rotated rectangle whereas [testing][Query::test] transforms positions into the local frame of the rectangle.
+    /// Zero `half_extents` are allowed and yield a degenerate rectangle, i.e. a line segment or a single point.
+    pub fn new(center: [T; 2], half_extents: [T; 2], angle: T) -> Self {
+        let half_extents = half_extents.map(|half_extent| half_extent.abs());
+        let (sin, cos) = angle.sin_cos();
 
-                        let mut results2 = Vec::new();
-                        index.look_up(&query, |object| {
-                            results2.push(object);
-                            ControlFlow::Continue(())
-                        });
+        let width = (half_extents[0] * cos).abs() + (half_extents[1] * sin).abs();
+        let height = (half_extents[0] * sin).abs() + (half_extents[1] * cos).abs();
 
-                        results1.sort_unstable();
-                        results2.sort_unstable();
-                        assert_eq!(results1, results2);
-                    }
+        Self {
+            aabb: (
+                [center[0] - width, center[1] - height],
+                [center[0] + width, center[1] + height],
+            ),
+            center,
+            half_extents,
+            cos,
+            sin,
+        }
+    }
+}
 
-                    Ok(())
-                },
-            )
-            .unwrap();
+impl<T> Query<[T; 2]> for WithinOrientedBox<T>
+where
+    T: Float,
+{
+    fn aabb(&self) -> &([T; 2], [T; 2]) {
+        &self.aabb
     }
 
-    #[cfg(feature = "rayon")]
-    #[test]
+    fn test(&self, position: &[T; 2]) -> bool {
+        let x = position[0] - self.center[0];
+        let y = position[1] - self.center[1];
+
+        let local_x = x * self.cos + y * self.sin;
+        let local_y = y * self.cos - x * self.sin;
+
+        local_x.abs() <= self.half_extents[0] && local_y.abs() <= self.half_extents[1]
+    }
+}
+
+/// A query which yields all objects within an axis-aligned ellipsoid in `N`-dimensional real space
+///
+/// Requires `T: Float` as testing a position divides the offset along each axis by the radius along that axis.
+#[derive(Debug)]
+pub struct WithinEllipsoid<T, const N: usize> {
+    aabb: ([T; N], [T; N]),
+    center: [T; N],
+    radii: [T; N],
+}
+
+impl<T, const N: usize> WithinEllipsoid<T, N>
+where
+    T: Float,
+{
+    /// Construct a query from the `center` and the `radii` along each axis
+    ///
+    /// All `radii` should be positive.
+    pub fn new(center: [T; N], radii: [T; N]) -> Self {
+        let mut aabb = (center, center);
+
+        for axis in 0..N {
+            aabb.0[axis] = center[axis] - radii[axis];
+            aabb.1[axis] = center[axis] + radii[axis];
+        }
+
+        Self {
+            aabb,
+            center,
+            radii,
+        }
+    }
+}
+
+impl<T, const N: usize> Query<[T; N]> for WithinEllipsoid<T, N>
+where
+    T: Float,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &[T; N]) -> bool {
+        let sum = (0..N).fold(T::zero(), |res, axis| {
+            let scaled = (position[axis] - self.center[axis]) / self.radii[axis];
+
+            res + scaled * scaled
+        });
+
+        sum <= T::one()
+    }
+}
+
+/// A query which yields all objects on one side of a hyperplane in `N`-dimensional space
+///
+/// A half-space is unbounded, but an infinite AABB would force the search to visit every object.
+/// Hence the query is clamped to a caller-supplied AABB which should be as tight as possible,
+/// e.g. the extent of the objects in the tree or the AABB of another query it is combined with like the near and far planes of a view frustum.
+#[derive(Debug)]
+pub struct OnSideOfPlane<T, const N: usize> {
+    aabb: ([T; N], [T; N]),
+    normal: [T; N],
+    offset: T,
+}
+
+impl<T, const N: usize> OnSideOfPlane<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    /// Construct a query matching all positions within `aabb` whose dot product with `normal` is at least `offset`
+    ///
+    /// The `aabb` is given by its corners with first the smallest and then the largest coordinate values.
+    pub fn new(normal: [T; N], offset: T, aabb: ([T; N], [T; N])) -> Self {
+        Self {
+            aabb,
+            normal,
+            offset,
+        }
+    }
+}
+
+impl<T, const N: usize> Query<[T; N]> for OnSideOfPlane<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &[T; N]) -> bool {
+        let dot = (0..N).fold(T::zero(), |res, axis| {
+            res + self.normal[axis] * position[axis]
+        });
+
+        dot >= self.offset
+    }
+}
+
+/// A query which yields all objects matching both of two queries
+///
+/// The AABB is the intersection of the operands' AABB which tightens the pruning compared to each of them.
+/// If their AABB do not overlap, the intersection is degenerate, i.e. its smallest coordinate values exceed its largest ones along some axis, so that no object is tested.
+#[derive(Debug)]
+pub struct And<A, B, P> {
+    aabb: (P, P),
+    lhs: A,
+    rhs: B,
+}
+
+impl<A, B, T, const N: usize> And<A, B, [T; N]>
+where
+    A: Query<[T; N]>,
+    B: Query<[T; N]>,
+    T: Num + Copy + PartialOrd,
+{
+    /// Construct a query matching the objects matched by both `lhs` and `rhs`
+    pub fn new(lhs: A, rhs: B) -> Self {
+        let (lhs_lower, lhs_upper) = lhs.aabb();
+        let (rhs_lower, rhs_upper) = rhs.aabb();
+
+        let mut aabb = (*lhs_lower, *lhs_upper);
+
+        for axis in 0..N {
+            if aabb.0[axis] < rhs_lower[axis] {
+                aabb.0[axis] = rhs_lower[axis];
+            }
+
+            if aabb.1[axis] > rhs_upper[axis] {
+                aabb.1[axis] = rhs_upper[axis];
+            }
+        }
+
+        Self { aabb, lhs, rhs }
+    }
+}
+
+impl<A, B, P> Query<P> for And<A, B, P>
+where
+    A: Query<P>,
+    B: Query<P>,
+    P: Point,
+{
+    fn aabb(&self) -> &(P, P) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &P) -> bool {
+        self.lhs.test(position) && self.rhs.test(position)
+    }
+}
+
+/// A query which yields all objects matching either of two queries
+///
+/// The AABB is the union of the operands' AABB and each operand is only tested for positions inside its own AABB.
+#[derive(Debug)]
+pub struct Or<A, B, P> {
+    aabb: (P, P),
+    lhs: A,
+    rhs: B,
+}
+
+impl<A, B, T, const N: usize> Or<A, B, [T; N]>
+where
+    A: Query<[T; N]>,
+    B: Query<[T; N]>,
+    T: Num + Copy + PartialOrd,
+{
+    /// Construct a query matching the objects matched by `lhs` or `rhs`
+    pub fn new(lhs: A, rhs: B) -> Self {
+        let (lhs_lower, lhs_upper) = lhs.aabb();
+        let (rhs_lower, rhs_upper) = rhs.aabb();
+
+        let mut aabb = (*lhs_lower, *lhs_upper);
+
+        for axis in 0..N {
+            if aabb.0[axis] > rhs_lower[axis] {
+                aabb.0[axis] = rhs_lower[axis];
+            }
+
+            if aabb.1[axis] < rhs_upper[axis] {
+                aabb.1[axis] = rhs_upper[axis];
+            }
+        }
+
+        Self { aabb, lhs, rhs }
+    }
+}
+
+impl<A, B, P> Query<P> for Or<A, B, P>
+where
+    A: Query<P>,
+    B: Query<P>,
+    P: Point,
+{
+    fn aabb(&self) -> &(P, P) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &P) -> bool {
+        (contains(self.lhs.aabb(), position) && self.lhs.test(position))
+            || (contains(self.rhs.aabb(), position) && self.rhs.test(position))
+    }
+}
+
+/// A query which yields all objects not matching a given query
+///
+/// The complement of a query is unbounded, so like for [`OnSideOfPlane`], the query is clamped to a caller-supplied AABB,
+/// e.g. the extent of the objects in the tree.
+#[derive(Debug)]
+pub struct Not<A, P> {
+    aabb: (P, P),
+    query: A,
+}
+
+impl<A, P> Not<A, P>
+where
+    A: Query<P>,
+    P: Point,
+{
+    /// Construct a query matching all positions within `aabb` which are not matched by `query`
+    ///
+    /// The `aabb` is given by its corners with first the smallest and then the largest coordinate values.
+    pub fn new(query: A, aabb: (P, P)) -> Self {
+        Self { aabb, query }
+    }
+}
+
+impl<A, P> Query<P> for Not<A, P>
+where
+    A: Query<P>,
+    P: Point,
+{
+    fn aabb(&self) -> &(P, P) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &P) -> bool {
+        !(contains(self.query.aabb(), position) && self.query.test(position))
+    }
+}
+
+#[cfg(feature = "std")]
+/// A query which yields all objects within a given distance to any of a set of points in `N`-dimensional real space
+///
+/// The points are organized into an internal [`KdTree`] so that testing a position only requires a nearest neighbour search.
+/// However, the AABB of the query spans all points which makes it ineffective for large or widely spread sets,
+/// for which a spatial join via [`KdTree::join_within`] is preferable.
+#[derive(Debug)]
+pub struct AtPositions<T, const N: usize> {
+    aabb: ([T; N], [T; N]),
+    points: KdTree<[T; N]>,
+    epsilon_2: T,
+}
+
+#[cfg(feature = "std")]
+impl<T, const N: usize> AtPositions<T, N>
+where
+    T: Float,
+{
+    /// Construct a query from the given `points` and the largest allowed Euclidean distance `epsilon` to any of them
+    pub fn new(points: &[[T; N]], epsilon: T) -> Self {
+        let mut aabb = ([T::infinity(); N], [T::neg_infinity(); N]);
+
+        for point in points {
+            for (axis, coord) in point.iter().enumerate() {
+                aabb.0[axis] = aabb.0[axis].min(*coord - epsilon);
+                aabb.1[axis] = aabb.1[axis].max(*coord + epsilon);
+            }
+        }
+
+        Self {
+            aabb,
+            points: KdTree::new(points.into()),
+            epsilon_2: epsilon * epsilon,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, const N: usize> Query<[T; N]> for AtPositions<T, N>
+where
+    T: Float,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        &self.aabb
+    }
+
+    fn test(&self, position: &[T; N]) -> bool {
+        self.points
+            .nearest(position)
+            .map_or(false, |point| point.distance_2(position) <= self.epsilon_2)
+    }
+}
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    /// Find objects matching the given `query`
+    ///
+    /// Queries are defined by passing an implementor of the [`Query`] trait.
+    ///
+    /// Objects matching the `query` are passed to the `visitor` as they are found.
+    /// Depending on its [return value][`ControlFlow`], the search is continued or stopped.
+    ///
+    /// Objects are visited in depth-first pre-order, i.e. each node before its left and then its right subtree.
+    /// Hence the order of the matches is determined by the layout of the tree, which is deterministic for a given sequence of objects passed to [`new`][Self::new],
+    /// but which is not guaranteed to be stable across versions of this crate or the standard library.
+    pub fn look_up<'a, Q, V>(&'a self, query: &Q, visitor: V) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        look_up_slice(self.objects.as_ref(), query, visitor)
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query` and collect them into a vector
+    ///
+    /// The matches are returned in the [order of traversal][Self::look_up].
+    /// Use [`look_up_into`][Self::look_up_into] to reuse the allocation for multiple queries.
+    pub fn look_up_collect<Q>(&self, query: &Q) -> Vec<&O>
+    where
+        Q: Query<O::Point>,
+    {
+        let mut results = Vec::new();
+
+        let _ = self.look_up(query, |object| {
+            results.push(object);
+
+            ControlFlow::Continue(())
+        });
+
+        results
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query`, as a lazy iterator
+    ///
+    /// The matches are yielded in the same [order of traversal][Self::look_up] as by the visitor-based version,
+    /// but the iterator can be combined with adapters like [`take`][Iterator::take] or [`filter_map`][Iterator::filter_map].
+    /// Instead of recursing, the iterator maintains an explicit stack of the subtrees which still need to be searched.
+    pub fn look_up_iter<'a, Q>(&'a self, query: &'a Q) -> impl Iterator<Item = &'a O> + 'a
+    where
+        Q: Query<O::Point>,
+    {
+        let objects = self.objects.as_ref();
+
+        let stack = if objects.is_empty() {
+            Vec::new()
+        } else {
+            vec![(objects, 0)]
+        };
+
+        LookUpIter { query, stack }
+    }
+
+    /// Count the objects matching the given `query`
+    ///
+    /// This avoids allocating memory for the matches compared to [`look_up_collect`][Self::look_up_collect].
+    pub fn count<Q>(&self, query: &Q) -> usize
+    where
+        Q: Query<O::Point>,
+    {
+        let mut count = 0;
+
+        let _ = self.look_up(query, |_object| {
+            count += 1;
+
+            ControlFlow::Continue(())
+        });
+
+        count
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query` and store them in `out`
+    ///
+    /// The vector `out` is cleared before the search and then filled with the matches in the [order of traversal][Self::look_up].
+    /// Reusing the same vector for multiple queries avoids repeatedly allocating memory for the results.
+    pub fn look_up_into<'a, Q>(&'a self, query: &Q, out: &mut Vec<&'a O>)
+    where
+        Q: Query<O::Point>,
+    {
+        out.clear();
+
+        let _ = self.look_up(query, |object| {
+            out.push(object);
+
+            ControlFlow::Continue(())
+        });
+    }
+
+    #[cfg(feature = "std")]
+    /// Find up to `k` objects matching the given `query`
+    ///
+    /// The traversal is stopped as soon as `k` matches were found.
+    /// Which matches are returned is determined by the [order of traversal][Self::look_up], i.e. they are neither the closest nor a uniform sample.
+    pub fn look_up_take<'a, Q>(&'a self, query: &Q, k: usize) -> Vec<&'a O>
+    where
+        Q: Query<O::Point>,
+    {
+        let mut results = Vec::new();
+
+        if k == 0 {
+            return results;
+        }
+
+        let _ = self.look_up(query, |object| {
+            results.push(object);
+
+            if results.len() < k {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+
+        results
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query`, reporting at most `cap_per_subtree` matches from each of the top-level subtrees
+    ///
+    /// The top-level subtrees are the left and right children of the root, i.e. the objects stored before and after the middle of the tree.
+    /// The root itself is always reported if it matches and is not counted against either cap.
+    /// Which matches are reported from each subtree is determined by the [order of traversal][Self::look_up].
+    pub fn look_up_capped<'a, Q>(&'a self, query: &Q, cap_per_subtree: usize) -> Vec<&'a O>
+    where
+        Q: Query<O::Point>,
+    {
+        let mut results = Vec::new();
+
+        let objects = self.objects.as_ref();
+
+        if objects.is_empty() {
+            return results;
+        }
+
+        let (left, object, right) = split(objects);
+
+        let position = object.position();
+
+        if contains(query.aabb(), position) && query.test(position) {
+            results.push(object);
+        }
+
+        if cap_per_subtree == 0 {
+            return results;
+        }
+
+        let search_left = query.aabb().0.coord(0) <= position.coord(0);
+        let search_right = position.coord(0) <= query.aabb().1.coord(0);
+
+        let axis = 1 % O::Point::DIM;
+
+        for (subtree, search) in [(left, search_left), (right, search_right)] {
+            if !search {
+                continue;
+            }
+
+            let mut count = 0;
+
+            let _ = look_up_at_axis(subtree, axis, query, |object| {
+                results.push(object);
+                count += 1;
+
+                if count < cap_per_subtree {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            });
+        }
+
+        results
+    }
+
+    /// Find objects matching the given `query` which are also admitted by the `allowed` predicate
+    ///
+    /// The predicate is called with the index of each spatially matching object in the tree, e.g. to check membership in a bit set of candidates determined by filtering other attributes.
+    /// The search is still pruned spatially, so the predicate is only called for objects matching the `query`.
+    pub fn look_up_among<'a, Q, F, V>(
+        &'a self,
+        query: &Q,
+        allowed: F,
+        mut visitor: V,
+    ) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        F: Fn(usize) -> bool,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        let objects = self.objects.as_ref();
+
+        self.look_up(query, |object| {
+            if allowed(index_of(objects, object)) {
+                visitor(object)?;
+            }
+
+            ControlFlow::Continue(())
+        })
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query` which are farther than `min_spacing` from each other
+    ///
+    /// A match is accepted only if its distance to all previously accepted matches is larger than `min_spacing`, yielding a subset similar to Poisson disk sampling.
+    /// The selection is greedy and hence depends on the [order of traversal][Self::look_up], i.e. it is maximal but not necessarily the largest possible one.
+    ///
+    /// Each match is compared against all accepted matches so that this is only efficient if `min_spacing` is large compared to the extent of the `query`.
+    pub fn look_up_spaced<'a, Q>(
+        &'a self,
+        query: &Q,
+        min_spacing: <O::Point as Point>::Coord,
+    ) -> Vec<&'a O>
+    where
+        O::Point: Distance,
+        Q: Query<O::Point>,
+    {
+        let min_spacing_2 = min_spacing * min_spacing;
+
+        let mut results = Vec::<&O>::new();
+
+        let _ = self.look_up(query, |object| {
+            let position = object.position();
+
+            if results
+                .iter()
+                .all(|result| result.position().distance_2(position) > min_spacing_2)
+            {
+                results.push(object);
+            }
+
+            ControlFlow::Continue(())
+        });
+
+        results
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query` and return them sorted by the given `key`
+    ///
+    /// All matches are collected before sorting them. The `key` is computed once per match and the sort is stable, i.e. matches with equal keys stay in the [order of traversal][Self::look_up].
+    pub fn look_up_sorted_by<'a, Q, K, F>(&'a self, query: &Q, key: F) -> Vec<&'a O>
+    where
+        Q: Query<O::Point>,
+        K: Ord,
+        F: Fn(&O) -> K,
+    {
+        let mut results = Vec::new();
+
+        let _ = self.look_up(query, |object| {
+            results.push(object);
+
+            ControlFlow::Continue(())
+        });
+
+        results.sort_by_cached_key(|object| key(object));
+
+        results
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query` and visit them in the order of their storage
+    ///
+    /// In contrast to [`look_up`][Self::look_up] which streams matches to the `visitor` as they are found,
+    /// this collects the positions of all matches first, sorts them and only then passes the objects to the `visitor` in ascending memory order.
+    /// This can improve cache behaviour if the `visitor` performs expensive work accessing the objects themselves,
+    /// at the cost of buffering all matches and sorting them before the first object is visited.
+    pub fn look_up_memory_order<'a, Q, V>(&'a self, query: &Q, mut visitor: V) -> ControlFlow<()>
+    where
+        Q: Query<O::Point>,
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        let objects = self.objects.as_ref();
+
+        let mut indices = Vec::new();
+
+        let _ = self.look_up(query, |object| {
+            indices.push(index_of(objects, object));
+
+            ControlFlow::Continue(())
+        });
+
+        indices.sort_unstable();
+
+        for index in indices {
+            visitor(&objects[index])?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    #[cfg(feature = "std")]
+    /// Find objects matching the given `query` and bucket them by the given `tile` function
+    ///
+    /// Each matching object is pushed into the bucket with the index `tile` returns for its position,
+    /// i.e. the result contains `num_tiles` buckets which each contain their matches in the [order of traversal][Self::look_up].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tile` returns an index which is not smaller than `num_tiles`.
+    pub fn look_up_bucketed<'a, Q, F>(
+        &'a self,
+        query: &Q,
+        tile: F,
+        num_tiles: usize,
+    ) -> Vec<Vec<&'a O>>
+    where
+        Q: Query<O::Point>,
+        F: Fn(&O::Point) -> usize,
+    {
+        let mut buckets = vec![Vec::new(); num_tiles];
+
+        let _ = self.look_up(query, |object| {
+            buckets[tile(object.position())].push(object);
+
+            ControlFlow::Continue(())
+        });
+
+        buckets
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Find objects matching the given `query`, in parallel
+    ///
+    /// Queries are defined by passing an implementor of the [`Query`] trait.
+    ///
+    /// Objects matching the `query` are passed to the `visitor` as they are found.
+    /// In contrast to the [serial version][Self::look_up], parts of the search can continue
+    /// even after it has been stopped.
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_look_up<'a, Q, V>(&'a self, query: &Q, visitor: V) -> ControlFlow<()>
+    where
+        O: Send + Sync,
+        O::Point: Sync,
+        Q: Query<O::Point> + Sync,
+        V: Fn(&'a O) -> ControlFlow<()> + Sync,
+    {
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            par_look_up(
+                &LookUpArgs {
+                    query,
+                    visitor,
+                    leaf_size: 1,
+                },
+                objects,
+                0,
+            )?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Count the objects matching the given `query`, in parallel
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_count<Q>(&self, query: &Q) -> usize
+    where
+        O: Send + Sync,
+        O::Point: Sync,
+        Q: Query<O::Point> + Sync,
+    {
+        let count = AtomicUsize::new(0);
+
+        let _ = self.par_look_up(query, |_object| {
+            count.fetch_add(1, Ordering::Relaxed);
+
+            ControlFlow::Continue(())
+        });
+
+        count.into_inner()
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Find objects matching the given `query`, as a parallel iterator
+    ///
+    /// In contrast to the [visitor-based version][Self::par_look_up], the matches are produced as a [parallel iterator][ParallelIterator]
+    /// which can be combined with further adapters like [`map`][ParallelIterator::map] or [`reduce`][ParallelIterator::reduce].
+    /// The search is split into independent tasks covering disjoint subtrees on demand.
+    ///
+    /// The order of the matches is not deterministic.
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_query_iter<'a, Q>(&'a self, query: &'a Q) -> impl ParallelIterator<Item = &'a O> + 'a
+    where
+        O: Sync,
+        Q: Query<O::Point> + Sync,
+    {
+        let objects = self.objects.as_ref();
+
+        let subtrees = if objects.is_empty() {
+            Vec::new()
+        } else {
+            vec![(objects, 0)]
+        };
+
+        iter::split(subtrees, move |subtrees| split_subtrees(query, subtrees)).flat_map_iter(
+            move |subtrees| {
+                let mut matches = Vec::new();
+
+                for (objects, axis) in subtrees {
+                    let _ = look_up_at_axis(objects, axis, query, |object| {
+                        matches.push(object);
+
+                        ControlFlow::Continue(())
+                    });
+                }
+
+                matches
+            },
+        )
+    }
+}
+
+impl<O, S, T, const N: usize> KdTree<O, S>
+where
+    O: Object<Point = [T; N]>,
+    T: Float,
+    S: AsRef<[O]>,
+{
+    /// Find objects matching the given `query` at time `t` assuming they move linearly from the positions at which the tree was built
+    ///
+    /// The position of each object at time `t` is computed as its position plus its `velocity` times `t`.
+    /// The speed of all objects, i.e. the Euclidean norm of their velocities, must not exceed `max_speed`.
+    /// The tree is then searched conservatively by enlarging the AABB of the `query` by `max_speed` times `t` along each axis
+    /// whereas the `query` itself is tested against the exact position at time `t`.
+    ///
+    /// This yields correct results without rebuilding the tree, but the search becomes less selective as `t` grows.
+    /// Objects which violate the speed bound might be missed.
+    pub fn look_up_moving<'a, Q, F, V>(
+        &'a self,
+        query: &Q,
+        t: T,
+        max_speed: T,
+        velocity: F,
+        mut visitor: V,
+    ) -> ControlFlow<()>
+    where
+        Q: Query<[T; N]>,
+        F: Fn(&O) -> [T; N],
+        V: FnMut(&'a O) -> ControlFlow<()>,
+    {
+        let displacement = (max_speed * t).abs();
+
+        let aabb = query.aabb();
+
+        let inflated = WithinBoundingBox::new(
+            aabb.0.map(|coord| coord - displacement),
+            aabb.1.map(|coord| coord + displacement),
+        );
+
+        self.look_up(&inflated, |object| {
+            let mut position = *object.position();
+
+            for (coord, velocity) in position.iter_mut().zip(velocity(object)) {
+                *coord = *coord + velocity * t;
+            }
+
+            if contains(aabb, &position) && query.test(&position) {
+                visitor(object)?;
+            }
+
+            ControlFlow::Continue(())
+        })
+    }
+}
+
+/// Find objects matching the given `query` in the given `objects`
+///
+/// This is equivalent to [`KdTree::look_up`] but operates directly on a slice of objects without wrapping it into a [`KdTree`].
+///
+/// The `objects` must already be sorted as a tree, for example by [`KdTree::new`].
+/// Supplying `objects` which are not actually sorted is safe but will lead to incorrect results.
+///
+/// This function does not allocate and hence can be used to query trees in borrowed storage without access to a heap.
+pub fn look_up_slice<'a, O, Q, V>(objects: &'a [O], query: &Q, visitor: V) -> ControlFlow<()>
+where
+    O: Object,
+    Q: Query<O::Point>,
+    V: FnMut(&'a O) -> ControlFlow<()>,
+{
+    look_up_at_axis(objects, 0, query, visitor)
+}
+
+pub(crate) fn look_up_at_axis<'a, O, Q, V>(
+    objects: &'a [O],
+    axis: usize,
+    query: &Q,
+    visitor: V,
+) -> ControlFlow<()>
+where
+    O: Object,
+    Q: Query<O::Point>,
+    V: FnMut(&'a O) -> ControlFlow<()>,
+{
+    look_up_buckets(objects, axis, 1, query, visitor)
+}
+
+/// Works like [`look_up_at_axis`] but scans subtrees of up to `leaf_size` objects linearly instead of splitting them
+pub(crate) fn look_up_buckets<'a, O, Q, V>(
+    objects: &'a [O],
+    axis: usize,
+    leaf_size: usize,
+    query: &Q,
+    mut visitor: V,
+) -> ControlFlow<()>
+where
+    O: Object,
+    Q: Query<O::Point>,
+    V: FnMut(&'a O) -> ControlFlow<()>,
+{
+    if objects.len() <= leaf_size.max(2) {
+        // Tiny trees are scanned directly in the order of traversal, i.e. the root before its left child.
+        for object in objects.iter().rev() {
+            let position = object.position();
+
+            if contains(query.aabb(), position) && query.test(position) {
+                visitor(object)?;
+            }
+        }
+    } else {
+        look_up(
+            &mut LookUpArgs {
+                query,
+                visitor,
+                leaf_size,
+            },
+            objects,
+            axis,
+        )?;
+    }
+
+    ControlFlow::Continue(())
+}
+
+struct LookUpArgs<'a, Q, V> {
+    query: &'a Q,
+    visitor: V,
+    leaf_size: usize,
+}
+
+fn look_up<'a, O, Q, V>(
+    args: &mut LookUpArgs<Q, V>,
+    mut objects: &'a [O],
+    mut axis: usize,
+) -> ControlFlow<()>
+where
+    O: Object,
+    Q: Query<O::Point>,
+    V: FnMut(&'a O) -> ControlFlow<()>,
+{
+    loop {
+        if objects.len() <= args.leaf_size {
+            // Buckets are not sorted and hence scanned linearly.
+            for object in objects {
+                let position = object.position();
+
+                if contains(args.query.aabb(), position) && args.query.test(position) {
+                    (args.visitor)(object)?;
+                }
+            }
+
+            return ControlFlow::Continue(());
+        }
+
+        let (left, object, right) = split(objects);
+
+        let position = object.position();
+
+        if contains(args.query.aabb(), position) && args.query.test(position) {
+            (args.visitor)(object)?;
+        }
+
+        let search_left = args.query.aabb().0.coord(axis) <= position.coord(axis);
+
+        let search_right = position.coord(axis) <= args.query.aabb().1.coord(axis);
+
+        axis = (axis + 1) % O::Point::DIM;
+
+        match (search_left, search_right) {
+            (true, true) => {
+                look_up(args, left, axis)?;
+
+                objects = right;
+            }
+            (true, false) => objects = left,
+            (false, true) => objects = right,
+            (false, false) => return ControlFlow::Continue(()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+struct LookUpIter<'a, O, Q> {
+    query: &'a Q,
+    stack: Vec<(&'a [O], usize)>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, O, Q> Iterator for LookUpIter<'a, O, Q>
+where
+    O: Object,
+    Q: Query<O::Point>,
+{
+    type Item = &'a O;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((objects, axis)) = self.stack.pop() {
+            let (left, object, right) = split(objects);
+
+            let position = object.position();
+
+            let next_axis = (axis + 1) % O::Point::DIM;
+
+            // The right subtree is pushed first so that the left one is searched first.
+            if !right.is_empty() && position.coord(axis) <= self.query.aabb().1.coord(axis) {
+                self.stack.push((right, next_axis));
+            }
+
+            if !left.is_empty() && self.query.aabb().0.coord(axis) <= position.coord(axis) {
+                self.stack.push((left, next_axis));
+            }
+
+            if contains(self.query.aabb(), position) && self.query.test(position) {
+                return Some(object);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn par_look_up<'a, O, Q, V>(
+    args: &LookUpArgs<Q, V>,
+    mut objects: &'a [O],
+    mut axis: usize,
+) -> ControlFlow<()>
+where
+    O: Object + Send + Sync,
+    O::Point: Sync,
+    Q: Query<O::Point> + Sync,
+    V: Fn(&'a O) -> ControlFlow<()> + Sync,
+{
+    loop {
+        if objects.len() <= args.leaf_size {
+            // Buckets are not sorted and hence scanned linearly.
+            for object in objects {
+                let position = object.position();
+
+                if contains(args.query.aabb(), position) && args.query.test(position) {
+                    (args.visitor)(object)?;
+                }
+            }
+
+            return ControlFlow::Continue(());
+        }
+
+        let (left, object, right) = split(objects);
+
+        let position = object.position();
+
+        if contains(args.query.aabb(), position) && args.query.test(position) {
+            (args.visitor)(object)?;
+        }
+
+        let search_left = args.query.aabb().0.coord(axis) <= position.coord(axis);
+
+        let search_right = position.coord(axis) <= args.query.aabb().1.coord(axis);
+
+        axis = (axis + 1) % O::Point::DIM;
+
+        match (search_left, search_right) {
+            (true, true) => {
+                let (left, right) = join(
+                    || par_look_up(args, left, axis),
+                    || par_look_up(args, right, axis),
+                );
+
+                left?;
+                right?;
+
+                return ControlFlow::Continue(());
+            }
+            (true, false) => objects = left,
+            (false, true) => objects = right,
+            (false, false) => return ControlFlow::Continue(()),
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[allow(clippy::type_complexity)]
+fn split_subtrees<'a, O, Q>(
+    query: &Q,
+    mut subtrees: Vec<(&'a [O], usize)>,
+) -> (Vec<(&'a [O], usize)>, Option<Vec<(&'a [O], usize)>>)
+where
+    O: Object,
+    Q: Query<O::Point>,
+{
+    if let [(objects, axis)] = subtrees[..] {
+        if objects.len() > 1 {
+            let (left, object, right) = split(objects);
+
+            let position = object.position();
+
+            let search_left =
+                !left.is_empty() && query.aabb().0.coord(axis) <= position.coord(axis);
+
+            let search_right =
+                !right.is_empty() && position.coord(axis) <= query.aabb().1.coord(axis);
+
+            let next_axis = (axis + 1) % O::Point::DIM;
+
+            subtrees.clear();
+            subtrees.push((slice::from_ref(object), axis));
+
+            if search_left {
+                subtrees.push((left, next_axis));
+            }
+
+            if search_right {
+                subtrees.push((right, next_axis));
+            }
+        }
+    }
+
+    if subtrees.len() < 2 {
+        return (subtrees, None);
+    }
+
+    let other = subtrees.split_off(subtrees.len() / 2);
+
+    (subtrees, Some(other))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use std::ptr;
+    #[cfg(feature = "rayon")]
+    use std::sync::Mutex;
+
+    use proptest::{arbitrary::any, collection::vec, test_runner::TestRunner};
+
+    use crate::tests::{
+        random_objects, random_objects_1d, random_points, random_queries, random_queries_1d,
+        RandomObject,
+    };
+
+    #[test]
+    fn random_look_up() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_manhattan() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, centers, distance)| {
+                    let index = KdTree::new(objects);
+
+                    for center in centers {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                (object.0[0] - center[0]).abs() + (object.0[1] - center[1]).abs()
+                                    <= distance
+                            })
+                            .collect::<Vec<_>>();
+
+                        let mut results2 =
+                            index.look_up_collect(&WithinManhattanDistance::new(center, distance));
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_chebyshev() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, centers, distance)| {
+                    let index = KdTree::new(objects);
+
+                    for center in centers {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                (0..2).all(|axis| {
+                                    center[axis] - distance <= object.0[axis]
+                                        && object.0[axis] <= center[axis] + distance
+                                })
+                            })
+                            .collect::<Vec<_>>();
+
+                        let mut results2 =
+                            index.look_up_collect(&WithinChebyshevDistance::new(center, distance));
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_on_side_of_plane() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_points(10),
+                    vec(-1.0_f32..=1.0, 10),
+                ),
+                |(objects, normals, offsets)| {
+                    let index = KdTree::new(objects);
+
+                    for (normal, offset) in normals.into_iter().zip(offsets) {
+                        let normal = normal.map(|coord| 2.0 * coord - 1.0);
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                normal[0] * object.0[0] + normal[1] * object.0[1] >= offset
+                            })
+                            .collect::<Vec<_>>();
+
+                        let query = OnSideOfPlane::new(normal, offset, ([0.0; 2], [1.0; 2]));
+
+                        let mut results2 = index.look_up_collect(&query);
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_ellipsoid() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), random_points(10)),
+                |(objects, centers, radii)| {
+                    let index = KdTree::new(objects);
+
+                    for (center, radii) in centers.into_iter().zip(radii) {
+                        let radii = radii.map(|radius| radius + 0.01);
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                let x = (object.0[0] - center[0]) / radii[0];
+                                let y = (object.0[1] - center[1]) / radii[1];
+
+                                x * x + y * y <= 1.0
+                            })
+                            .collect::<Vec<_>>();
+
+                        let mut results2 =
+                            index.look_up_collect(&WithinEllipsoid::new(center, radii));
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_combinators() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), random_queries(10)),
+                |(objects, lhs, rhs)| {
+                    let index = KdTree::new(objects);
+
+                    let matches = |query: &WithinDistance<f32, 2>, object: &RandomObject| {
+                        contains(query.aabb(), &object.0) && query.test(&object.0)
+                    };
+
+                    for (lhs, rhs) in lhs.into_iter().zip(rhs) {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| matches(&lhs, object) && matches(&rhs, object))
+                            .collect::<Vec<_>>();
+                        let mut results2 = index.look_up_collect(&And::new(&lhs, &rhs));
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| matches(&lhs, object) || matches(&rhs, object))
+                            .collect::<Vec<_>>();
+                        let mut results2 = index.look_up_collect(&Or::new(&lhs, &rhs));
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| !matches(&lhs, object))
+                            .collect::<Vec<_>>();
+                        let mut results2 =
+                            index.look_up_collect(&Not::new(&lhs, ([0.0; 2], [1.0; 2])));
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_collect() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = index.look_up_collect(&query);
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_into() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    let mut results2 = Vec::new();
+
+                    for query in queries {
+                        let mut results1 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results1.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        index.look_up_into(&query, &mut results2);
+
+                        assert_eq!(results1, results2);
+
+                        let mut results3 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        results1.sort_unstable();
+                        results3.sort_unstable();
+                        assert_eq!(results1, results3);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_count() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let count = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .count();
+
+                        assert_eq!(index.count(&query), count);
+                        #[cfg(feature = "rayon")]
+                        assert_eq!(index.par_count(&query), count);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_iter() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results1.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        let results2 = index.look_up_iter(&query).collect::<Vec<_>>();
+
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_take() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), 0_usize..20),
+                |(objects, queries, k)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results1.push(object);
+                            ControlFlow::Continue(())
+                        });
+                        results1.truncate(k);
+
+                        let results2 = index.look_up_take(&query, k);
+
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_capped() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), 0_usize..20),
+                |(objects, queries, cap)| {
+                    let index = KdTree::new(objects);
+
+                    let mid = index.len() / 2;
+
+                    for query in queries {
+                        let matches = |range: &[RandomObject]| {
+                            range
+                                .iter()
+                                .filter(|object| query.test(object.position()))
+                                .count()
+                        };
+
+                        let results = index.look_up_capped(&query, cap);
+
+                        assert!(results.iter().all(|object| query.test(object.position())));
+
+                        let indices = results
+                            .iter()
+                            .map(|object| index_of(&index, object))
+                            .collect::<Vec<_>>();
+
+                        assert_eq!(
+                            indices.iter().filter(|index| **index < mid).count(),
+                            matches(&index[..mid]).min(cap)
+                        );
+                        assert_eq!(
+                            indices.iter().filter(|index| **index == mid).count(),
+                            matches(&index[mid..=mid])
+                        );
+                        assert_eq!(
+                            indices.iter().filter(|index| **index > mid).count(),
+                            matches(&index[mid + 1..]).min(cap)
+                        );
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_among() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_queries(10),
+                    vec(any::<bool>(), 100),
+                ),
+                |(objects, queries, allowed)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .zip(&allowed)
+                            .filter(|(object, allowed)| **allowed && query.test(object.position()))
+                            .map(|(object, _allowed)| object)
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up_among(
+                            &query,
+                            |index| allowed[index],
+                            |object| {
+                                results2.push(object);
+                                ControlFlow::Continue(())
+                            },
+                        );
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_moving() {
+        fn velocity(object: &RandomObject) -> [f32; 2] {
+            [0.2 * (object.0[1] - 0.5), 0.2 * (0.5 - object.0[0])]
+        }
+
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), -1.0_f32..=1.0),
+                |(objects, queries, t)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                let velocity = velocity(object);
+
+                                query.test(&[
+                                    object.0[0] + velocity[0] * t,
+                                    object.0[1] + velocity[1] * t,
+                                ])
+                            })
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up_moving(&query, t, 0.2, velocity, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_spaced() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), 0.0_f32..=0.5),
+                |(objects, queries, min_spacing)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let results = index.look_up_spaced(&query, min_spacing);
+
+                        let spaced = |lhs: &RandomObject, rhs: &RandomObject| {
+                            lhs.0.distance_2(&rhs.0) > min_spacing * min_spacing
+                        };
+
+                        for (pos, lhs) in results.iter().enumerate() {
+                            assert!(query.test(lhs.position()));
+
+                            for rhs in &results[pos + 1..] {
+                                assert!(spaced(lhs, rhs));
+                            }
+                        }
+
+                        for object in index.iter() {
+                            if query.test(object.position()) {
+                                assert!(results
+                                    .iter()
+                                    .any(|result| ptr::eq(*result, object)
+                                        || !spaced(result, object)));
+                            }
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_sorted_by() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    let key = |object: &RandomObject| (object.0[1] * 10.0) as i32;
+
+                    for query in queries {
+                        let mut results1 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results1.push(object);
+                            ControlFlow::Continue(())
+                        });
+                        results1.sort_by_key(|object| key(object));
+
+                        let results2 = index.look_up_sorted_by(&query, key);
+
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_memory_order() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up_memory_order(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_bucketed() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_queries(10), 1_usize..10),
+                |(objects, queries, num_tiles)| {
+                    let index = KdTree::new(objects);
+
+                    let tile = |position: &[f32; 2]| {
+                        ((position[0] * num_tiles as f32) as usize).min(num_tiles - 1)
+                    };
+
+                    for query in queries {
+                        let buckets = index.look_up_bucketed(&query, tile, num_tiles);
+
+                        assert_eq!(buckets.len(), num_tiles);
+
+                        for (idx, bucket) in buckets.into_iter().enumerate() {
+                            let mut results1 = index
+                                .iter()
+                                .filter(|object| {
+                                    query.test(object.position()) && tile(object.position()) == idx
+                                })
+                                .collect::<Vec<_>>();
+
+                            let mut results2 = bucket;
+
+                            results1.sort_unstable();
+                            results2.sort_unstable();
+                            assert_eq!(results1, results2);
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_tiny() {
+        for len in 0..=2 {
+            TestRunner::default()
+                .run(
+                    &(random_objects(len), random_queries(10)),
+                    |(objects, queries)| {
+                        let index = KdTree::new(objects);
+
+                        for query in queries {
+                            let results1 = index
+                                .iter()
+                                .rev()
+                                .filter(|object| query.test(object.position()))
+                                .collect::<Vec<_>>();
+
+                            let mut results2 = Vec::new();
+                            let _ = index.look_up(&query, |object| {
+                                results2.push(object);
+                                ControlFlow::Continue(())
+                            });
+
+                            assert_eq!(results1, results2);
+                        }
+
+                        Ok(())
+                    },
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn random_look_up_1d() {
+        TestRunner::default()
+            .run(
+                &(random_objects_1d(100), random_queries_1d(10)),
+                |(objects, queries)| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_with_epsilon() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), random_points(10)),
+                |(objects, lowers, uppers)| {
+                    let index = KdTree::new(objects);
+
+                    for (lower, upper) in lowers.into_iter().zip(uppers) {
+                        let query = WithinBoundingBox::with_epsilon(lower, upper, 0.1);
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| contains(&(lower, upper), object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_tracked() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_points(10),
+                    vec(0.0_f32..=1.0, 10),
+                ),
+                |(objects, centers, distances)| {
+                    let index = KdTree::new(objects);
+
+                    for (center, distance) in centers.into_iter().zip(distances) {
+                        let query = WithinDistanceTracked::new(center, distance);
+
+                        let _ = index.look_up(&query, |object| {
+                            assert_eq!(query.last_distance_2(), center.distance_2(&object.0));
+                            ControlFlow::Continue(())
+                        });
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_subspace() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_queries(10),
+                    (any::<bool>(), any::<bool>()),
+                ),
+                |(objects, queries, (x, y))| {
+                    let index = KdTree::new(objects);
+
+                    for query in queries {
+                        let distance = query.distance_2.sqrt();
+                        let query = WithinDistanceSubspace::new(query.center, distance, [x, y]);
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                let diff_x = if x {
+                                    object.0[0] - query.center[0]
+                                } else {
+                                    0.0
+                                };
+                                let diff_y = if y {
+                                    object.0[1] - query.center[1]
+                                } else {
+                                    0.0
+                                };
+
+                                diff_x * diff_x + diff_y * diff_y <= distance * distance
+                            })
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_at_positions() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(5), 0.0_f32..=0.2),
+                |(objects, points, epsilon)| {
+                    let index = KdTree::new(objects);
+
+                    let query = AtPositions::new(&points, epsilon);
+
+                    let mut results1 = index
+                        .iter()
+                        .filter(|object| {
+                            points
+                                .iter()
+                                .any(|point| point.distance_2(&object.0) <= epsilon * epsilon)
+                        })
+                        .collect::<Vec<_>>();
+
+                    let mut results2 = Vec::new();
+                    let _ = index.look_up(&query, |object| {
+                        results2.push(object);
+                        ControlFlow::Continue(())
+                    });
+
+                    results1.sort_unstable();
+                    results2.sort_unstable();
+                    assert_eq!(results1, results2);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_look_up_oriented_box() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_points(10),
+                    random_points(10),
+                    vec(0.0_f32..=6.3, 10),
+                ),
+                |(objects, centers, half_extents, angles)| {
+                    let index = KdTree::new(objects);
+
+                    for ((center, half_extents), angle) in
+                        centers.into_iter().zip(half_extents).zip(angles)
+                    {
+                        let query = WithinOrientedBox::new(
+                            center,
+                            half_extents.map(|half_extent| half_extent / 2.0),
+                            angle,
+                        );
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
     fn random_par_look_up() {
         TestRunner::default()
             .run(
@@ -303,15 +2351,19 @@ mod tests {
                             .collect::<Vec<_>>();
 
                         let results2 = Mutex::new(Vec::new());
-                        index.par_look_up(&query, |object| {
+                        let _ = index.par_look_up(&query, |object| {
                             results2.lock().unwrap().push(object);
                             ControlFlow::Continue(())
                         });
                         let mut results2 = results2.into_inner().unwrap();
 
+                        let mut results3 = index.par_query_iter(&query).collect::<Vec<_>>();
+
                         results1.sort_unstable();
                         results2.sort_unstable();
+                        results3.sort_unstable();
                         assert_eq!(results1, results2);
+                        assert_eq!(results1, results3);
                     }
 
                     Ok(())
@@ -0,0 +1,139 @@
+use num_traits::Float;
+
+use crate::{nearest::nearest_slice_by_metric, Distance, KdTree, Object, Point};
+
+/// Defines a distance metric which is passed to [`KdTree::nearest_by_metric`] instead of being fixed by the [`Point`] type
+///
+/// Like [`Distance::distance_2`], only the relation between two distance values is required,
+/// so implementations can return any monotonic transformation of the actual distance, e.g. its square.
+pub trait Metric<P: Point> {
+    /// Return the transformed distance between `lhs` and `rhs`
+    fn distance(&self, lhs: &P, rhs: &P) -> P::Coord;
+
+    /// Return a lower bound of the transformed distance between `target` and any point whose coordinate value along the given `axis` is `coord` or beyond
+    ///
+    /// This is called during nearest neighbour search to prune subtrees and hence must be consistent with [`distance`][Self::distance].
+    fn axis_distance(&self, target: &P, axis: usize, coord: P::Coord) -> P::Coord;
+}
+
+/// The metric defined by the [`Distance`] implementation of the points, i.e. Euclidean distance for arrays
+///
+/// Searching with this metric yields the same results as [`KdTree::nearest`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Euclidean;
+
+impl<P> Metric<P> for Euclidean
+where
+    P: Distance,
+{
+    fn distance(&self, lhs: &P, rhs: &P) -> P::Coord {
+        lhs.distance_2(rhs)
+    }
+
+    fn axis_distance(&self, target: &P, axis: usize, coord: P::Coord) -> P::Coord {
+        target.axis_distance_2(axis, coord)
+    }
+}
+
+/// The [Minkowski distance](https://en.wikipedia.org/wiki/Minkowski_distance) of the given order `p` for `N`-dimensional real space
+///
+/// The distance is computed as the sum of the `p`-th powers of the absolute differences of the coordinate values, i.e. without taking the `p`-th root.
+/// In contrast to [`MinkowskiDistance`][crate::MinkowskiDistance], the order is chosen per search instead of being fixed by the point type and can be fractional.
+///
+/// The order `p` should be at least one for this to be a metric.
+#[derive(Debug, Clone, Copy)]
+pub struct Minkowski<T>(pub T);
+
+impl<T, const N: usize> Metric<[T; N]> for Minkowski<T>
+where
+    T: Float,
+{
+    fn distance(&self, lhs: &[T; N], rhs: &[T; N]) -> T {
+        lhs.iter().zip(rhs).fold(T::zero(), |res, (lhs, rhs)| {
+            res + (*lhs - *rhs).abs().powf(self.0)
+        })
+    }
+
+    fn axis_distance(&self, target: &[T; N], axis: usize, coord: T) -> T {
+        (target[axis] - coord).abs().powf(self.0)
+    }
+}
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object,
+    <O::Point as Point>::Coord: Float,
+    S: AsRef<[O]>,
+{
+    /// Find the object nearest to the given `target` using the given `metric`
+    ///
+    /// This performs the same search as [`nearest`][Self::nearest] but determines distances and prunes subtrees using the given `metric`,
+    /// hence it yields the same result as [`nearest`][Self::nearest] when using [`Euclidean`].
+    pub fn nearest_by_metric<M>(&self, target: &O::Point, metric: &M) -> Option<&O>
+    where
+        M: Metric<O::Point>,
+    {
+        nearest_slice_by_metric(self.objects.as_ref(), target, metric, 1)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::tests::{random_objects, random_points};
+
+    #[test]
+    fn random_nearest_by_euclidean_metric() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let result1 = index.nearest(&target);
+                        let result2 = index.nearest_by_metric(&target, &Euclidean);
+
+                        assert_eq!(
+                            result1.map(|object| object.0.distance_2(&target)),
+                            result2.map(|object| object.0.distance_2(&target))
+                        );
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_by_minkowski_metric() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 1.0_f32..=4.0),
+                |(objects, targets, p)| {
+                    let index = KdTree::new(objects);
+                    let metric = Minkowski(p);
+
+                    for target in targets {
+                        let result1 = index
+                            .iter()
+                            .map(|object| metric.distance(&target, &object.0))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+                        let result2 = index
+                            .nearest_by_metric(&target, &metric)
+                            .map(|object| metric.distance(&target, &object.0));
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
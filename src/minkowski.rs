@@ -0,0 +1,121 @@
+use crate::{Distance, Point};
+
+/// An `N`-dimensional point using the [Minkowski distance](https://en.wikipedia.org/wiki/Minkowski_distance) of order `P`
+///
+/// The [distance][Distance::distance_2] between two points is computed as the sum of the `P`-th powers of the absolute differences of their coordinate values.
+/// Since taking the `P`-th root is monotonic, comparing these sums preserves the ordering of the actual distances so that no root needs to be computed.
+///
+/// The order is part of the type so that all points in a tree and the targets used to search it necessarily agree on it.
+/// It should be at least one for this to be a metric.
+/// For fractional orders or to choose the order per search, use [`Minkowski`][crate::Minkowski] with [`KdTree::nearest_by_metric`][crate::KdTree::nearest_by_metric].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MinkowskiDistance<const N: usize, const P: u32> {
+    /// The coordinate values of the point
+    pub position: [f64; N],
+}
+
+impl<const N: usize, const P: u32> Point for MinkowskiDistance<N, P> {
+    const DIM: usize = N;
+
+    type Coord = f64;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self.position[axis]
+    }
+}
+
+/// Sum of the `P`-th powers of the absolute differences of the coordinate values
+impl<const N: usize, const P: u32> Distance for MinkowskiDistance<N, P> {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        self.position
+            .iter()
+            .zip(&other.position)
+            .map(|(lhs, rhs)| (lhs - rhs).abs().powi(P as i32))
+            .sum()
+    }
+
+    fn axis_distance_2(&self, axis: usize, coord: Self::Coord) -> Self::Coord {
+        (self.position[axis] - coord).abs().powi(P as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::{
+        tests::{assert_nearest_matches_brute_force, random_points},
+        KdTree, Metric, Minkowski, Object,
+    };
+
+    struct Sample<const P: u32>(MinkowskiDistance<2, P>);
+
+    impl<const P: u32> Object for Sample<P> {
+        type Point = MinkowskiDistance<2, P>;
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    fn check_nearest<const P: u32>(points: Vec<[f32; 2]>, targets: Vec<[f32; 2]>) {
+        let point = |position: [f32; 2]| MinkowskiDistance::<2, P> {
+            position: position.map(f64::from),
+        };
+
+        let index = KdTree::new(
+            points
+                .into_iter()
+                .map(|position| Sample(point(position)))
+                .collect::<Box<[_]>>(),
+        );
+
+        for target in &targets {
+            let target = point(*target);
+
+            let result1 = index
+                .iter()
+                .map(|sample| target.distance_2(&sample.0))
+                .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+            let result2 = index
+                .nearest(&target)
+                .map(|sample| target.distance_2(&sample.0));
+
+            assert_eq!(result1, result2);
+
+            // The per-search metric of the same order yields the same distance.
+            let metric = Minkowski(P as f64);
+
+            let result3 = index
+                .iter()
+                .map(|sample| metric.distance(&target.position, &sample.0.position))
+                .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+            assert!((result2.unwrap() - result3.unwrap()).abs() <= 1e-9);
+        }
+
+        assert_nearest_matches_brute_force(
+            index.iter().map(|sample| sample.0).collect(),
+            &targets.into_iter().map(point).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn random_minkowski_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_points(10)),
+                |(points, targets)| {
+                    check_nearest::<1>(points.clone(), targets.clone());
+                    check_nearest::<2>(points.clone(), targets.clone());
+                    check_nearest::<3>(points, targets);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
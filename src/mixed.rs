@@ -0,0 +1,142 @@
+use crate::{Distance, Point};
+
+/// A three-dimensional point combining two floating-point coordinates with an integer coordinate
+///
+/// Since all coordinate values of a [`Point`] share a single type, this cannot be expressed using `[T; 3]` without converting the integer coordinate beforehand.
+/// This type stores the integer exactly and exposes it as `f64` only when its [coordinate value][Point::coord] is accessed.
+///
+/// The conversion is exact for integers up to 2<sup>53</sup> in magnitude.
+/// Larger integers are rounded, but their order is preserved so that construction and queries remain correct even though distinct integers can compare as equal.
+/// For nearest neighbour search, the pruning bound along the integer axis is widened by the rounding error so that it never exceeds the exactly computed distance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixedPoint {
+    /// The floating-point coordinates along the first two axes
+    pub position: [f64; 2],
+    /// The integer coordinate along the third axis
+    pub category: i64,
+}
+
+impl Point for MixedPoint {
+    const DIM: usize = 3;
+
+    type Coord = f64;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        match axis {
+            0 | 1 => self.position[axis],
+            2 => self.category as f64,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Euclidean distance over all three axes with the difference of the integer coordinates computed exactly
+impl Distance for MixedPoint {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        let diff = (self.category as i128 - other.category as i128) as f64;
+
+        self.position.distance_2(&other.position) + diff * diff
+    }
+
+    fn axis_distance_2(&self, axis: usize, coord: Self::Coord) -> Self::Coord {
+        if axis != 2 {
+            let diff = self.position[axis] - coord;
+
+            return diff * diff;
+        }
+
+        // `coord` is a rounded integer, so the exact integers it represents differ from it by at most one unit in the last place.
+        let coord = coord as i128;
+
+        let bits = 128 - coord.unsigned_abs().leading_zeros();
+        let rounding = if bits > 53 { 1 << (bits - 53) } else { 0 };
+
+        let diff = ((self.category as i128 - coord).abs() - rounding).max(0) as f64;
+
+        diff * diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, strategy::Strategy, test_runner::TestRunner};
+
+    use crate::{tests::assert_nearest_matches_brute_force, KdTree, Object};
+
+    struct Sample(MixedPoint);
+
+    impl Object for Sample {
+        type Point = MixedPoint;
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn mixed_points() {
+        let index = KdTree::new(
+            (0..100)
+                .map(|idx| {
+                    Sample(MixedPoint {
+                        position: [idx as f64 / 10.0, (idx % 7) as f64],
+                        category: (1 << 60) + idx % 3,
+                    })
+                })
+                .collect::<Box<[_]>>(),
+        );
+
+        let target = MixedPoint {
+            position: [5.0, 3.0],
+            category: 1 << 60,
+        };
+
+        let nearest = index.nearest(&target).unwrap();
+        assert_eq!(nearest.0.position, [4.5, 3.0]);
+        assert_eq!(nearest.0.category, 1 << 60);
+    }
+
+    #[test]
+    fn random_large_categories() {
+        let mixed_points = |len| {
+            vec((0.0_f64..=1.0, 0.0_f64..=1.0, -8_i64..=8), len).prop_map(|points| {
+                points
+                    .into_iter()
+                    .map(|(x, y, offset)| MixedPoint {
+                        position: [x, y],
+                        category: (1 << 53) + offset,
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        TestRunner::default()
+            .run(
+                &(mixed_points(100), mixed_points(10)),
+                |(points, targets)| {
+                    assert_nearest_matches_brute_force(points, &targets);
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn rounded_category_bound() {
+        let target = MixedPoint {
+            position: [0.0, 0.0],
+            category: (1 << 53) + 3,
+        };
+
+        let other = MixedPoint {
+            position: [0.0, 0.0],
+            category: (1 << 53) - 1,
+        };
+
+        assert_eq!(target.distance_2(&other), 16.0);
+        assert!(target.axis_distance_2(2, other.coord(2)) <= 16.0);
+    }
+}
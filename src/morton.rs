@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+use num_traits::PrimInt;
+
+use crate::{KdTree, Object, Point};
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object,
+    <O::Point as Point>::Coord: PrimInt,
+    S: AsRef<[O]>,
+{
+    /// Return references to all objects ordered along the [Z-order curve](https://en.wikipedia.org/wiki/Z-order_curve)
+    ///
+    /// The objects are ordered by their Morton codes, i.e. by interleaving the bits of their coordinate values with the first axis being the most significant one at each bit position.
+    /// Signed coordinate values are ordered as if offset into the unsigned range.
+    ///
+    /// Note that this ordering is unrelated to the internal layout of the tree and is computed by sorting the objects without materializing their Morton codes.
+    pub fn morton_order(&self) -> Vec<&O> {
+        let mut objects = self.objects.as_ref().iter().collect::<Vec<_>>();
+
+        objects.sort_by(|lhs, rhs| cmp_morton(lhs.position(), rhs.position()));
+
+        objects
+    }
+}
+
+fn cmp_morton<P>(lhs: &P, rhs: &P) -> Ordering
+where
+    P: Point,
+    P::Coord: PrimInt,
+{
+    let mut msb_axis = 0;
+    let mut msb_zeros = u32::MAX;
+
+    for axis in 0..P::DIM {
+        let zeros = (lhs.coord(axis) ^ rhs.coord(axis)).leading_zeros();
+
+        if msb_zeros > zeros {
+            msb_zeros = zeros;
+            msb_axis = axis;
+        }
+    }
+
+    let lhs = lhs.coord(msb_axis);
+    let rhs = rhs.coord(msb_axis);
+
+    lhs.partial_cmp(&rhs).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    struct Cell([u8; 2]);
+
+    impl Object for Cell {
+        type Point = [u8; 2];
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    fn morton_code([x, y]: [u8; 2]) -> u16 {
+        (0..8).fold(0, |code, bit| {
+            let x = (x as u16 >> bit) & 1;
+            let y = (y as u16 >> bit) & 1;
+
+            code | (x << (2 * bit + 1)) | (y << (2 * bit))
+        })
+    }
+
+    #[test]
+    fn random_morton_order() {
+        TestRunner::default()
+            .run(&vec((0_u8.., 0_u8..), 100), |cells| {
+                let index = KdTree::new(
+                    cells
+                        .into_iter()
+                        .map(|(x, y)| Cell([x, y]))
+                        .collect::<Box<[_]>>(),
+                );
+
+                let codes = index
+                    .morton_order()
+                    .into_iter()
+                    .map(|cell| morton_code(cell.0))
+                    .collect::<Vec<_>>();
+
+                assert!(codes.windows(2).all(|codes| codes[0] <= codes[1]));
+
+                Ok(())
+            })
+            .unwrap();
+    }
+}
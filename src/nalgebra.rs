@@ -0,0 +1,75 @@
+use nalgebra::{Const, OPoint, Scalar};
+use num_traits::Num;
+
+use crate::{Distance, Point};
+
+/// `D`-dimensional space using Euclidean distance, including [`Point2`][nalgebra::Point2] and [`Point3`][nalgebra::Point3]
+///
+/// Requires the `nalgebra` feature.
+impl<T, const D: usize> Point for OPoint<T, Const<D>>
+where
+    T: Scalar + Num + Copy + PartialOrd,
+{
+    const DIM: usize = D;
+
+    type Coord = T;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self[axis]
+    }
+}
+
+impl<T, const D: usize> Distance for OPoint<T, Const<D>>
+where
+    T: Scalar + Num + Copy + PartialOrd,
+{
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        self.iter()
+            .zip(other.iter())
+            .fold(T::zero(), |res, (lhs, rhs)| {
+                let diff = *lhs - *rhs;
+
+                res + diff * diff
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point2, Point3};
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    use crate::tests::assert_nearest_matches_brute_force;
+
+    #[test]
+    fn random_nalgebra_nearest() {
+        TestRunner::default()
+            .run(
+                &(
+                    vec([0.0_f32..=1.0, 0.0_f32..=1.0, 0.0_f32..=1.0], 100),
+                    vec([0.0_f32..=1.0, 0.0_f32..=1.0, 0.0_f32..=1.0], 10),
+                ),
+                |(points, targets)| {
+                    assert_nearest_matches_brute_force(
+                        points.iter().map(|&[x, y, _z]| Point2::new(x, y)).collect(),
+                        &targets
+                            .iter()
+                            .map(|&[x, y, _z]| Point2::new(x, y))
+                            .collect::<Vec<_>>(),
+                    );
+
+                    assert_nearest_matches_brute_force(
+                        points.iter().copied().map(Point3::from).collect(),
+                        &targets
+                            .iter()
+                            .copied()
+                            .map(Point3::from)
+                            .collect::<Vec<_>>(),
+                    );
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
@@ -1,8 +1,20 @@
-use std::mem::swap;
+#[cfg(feature = "std")]
+use core::cmp::Ordering;
+use core::mem::swap;
+use core::ops::ControlFlow;
+#[cfg(feature = "std")]
+use core::ptr;
+#[cfg(feature = "std")]
+use std::collections::BinaryHeap;
 
-use num_traits::Float;
+use num_traits::{Float, One};
+#[cfg(feature = "rayon")]
+use rayon::{
+    iter::{IntoParallelRefIterator, ParallelIterator},
+    join,
+};
 
-use crate::{split, Distance, KdTree, Object, Point};
+use crate::{contains, index_of, split, Distance, Euclidean, KdTree, Metric, Object, Point};
 
 impl<O, S> KdTree<O, S>
 where
@@ -17,88 +29,1843 @@ where
     ///
     /// Returns `None` if the tree is empty or if no object has a finite distance to the `target`.
     pub fn nearest(&self, target: &O::Point) -> Option<&O> {
+        nearest_slice(self.objects.as_ref(), target)
+    }
+
+    /// Find the object nearest to the given `target` within the given `max_distance`
+    ///
+    /// This is equivalent to filtering the result of [`nearest`][Self::nearest] by whether it is at most `max_distance` away from the `target`,
+    /// but subtrees farther away than `max_distance` are pruned from the start.
+    pub fn nearest_within(
+        &self,
+        target: &O::Point,
+        max_distance: <O::Point as Point>::Coord,
+    ) -> Option<&O> {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: Within {
+                max_distance_2: max_distance * max_distance,
+                candidates: BestMatch {
+                    distance_2: <O::Point as Point>::Coord::infinity(),
+                    best_match: None,
+                },
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        args.candidates.candidates.best_match
+    }
+
+    /// Find the object nearest to the given `target` which matches the given `predicate`
+    ///
+    /// Subtrees are still pruned geometrically, but only matching objects tighten the pruning radius so that the nearest matching object is not missed.
+    pub fn nearest_matching<F>(&self, target: &O::Point, predicate: F) -> Option<&O>
+    where
+        F: Fn(&O) -> bool,
+    {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: Matching {
+                predicate,
+                candidates: BestMatch {
+                    distance_2: <O::Point as Point>::Coord::infinity(),
+                    best_match: None,
+                },
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        args.candidates.candidates.best_match
+    }
+
+    /// Find the object nearest to the given `target` which is not contained in any of the `blocked` regions
+    ///
+    /// The regions are given as AABBs and only exclude objects from the result, i.e. subtrees are still pruned geometrically as for [`nearest_matching`][Self::nearest_matching].
+    /// Each object encountered during the search is checked against all regions so that many regions make the search more expensive.
+    pub fn nearest_excluding_regions(
+        &self,
+        target: &O::Point,
+        blocked: &[(O::Point, O::Point)],
+    ) -> Option<&O> {
+        self.nearest_matching(target, |object| {
+            !blocked.iter().any(|aabb| contains(aabb, object.position()))
+        })
+    }
+
+    #[cfg(feature = "std")]
+    /// Find all objects which are nearest to the given `target` up to the relative tolerance `relative_eps`
+    ///
+    /// Returns every object whose squared distance is at most `1 + relative_eps` times the minimal squared distance,
+    /// i.e. whose distance is at most `sqrt(1 + relative_eps)` times the minimal distance, which is approximately `1 + relative_eps / 2` for small values.
+    /// With `relative_eps` equal to zero, exactly the objects tied for the minimal distance are returned.
+    ///
+    /// The objects are ordered by increasing distance with ties in unspecified order.
+    pub fn nearest_ties_eps(
+        &self,
+        target: &O::Point,
+        relative_eps: <O::Point as Point>::Coord,
+    ) -> Vec<&O> {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: Ties {
+                factor: <O::Point as Point>::Coord::one() + relative_eps,
+                distance_2: <O::Point as Point>::Coord::infinity(),
+                ties: Vec::new(),
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        let mut ties = args.candidates.ties;
+
+        ties.sort_unstable_by(|lhs, rhs| lhs.0.partial_cmp(&rhs.0).unwrap());
+
+        ties.into_iter()
+            .map(|(_distance_2, object)| object)
+            .collect()
+    }
+
+    /// Find an object approximately nearest to the given `target`
+    ///
+    /// Subtrees are pruned more aggressively by treating them as `1 + epsilon` times farther away than they actually are.
+    /// Hence the squared distance of the result is at most `1 + epsilon` times the squared distance of the nearest object, and so is its distance.
+    /// With `epsilon` equal to zero, the result is the same as the one of [`nearest`][Self::nearest].
+    pub fn nearest_approx(
+        &self,
+        target: &O::Point,
+        epsilon: <O::Point as Point>::Coord,
+    ) -> Option<&O> {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: Approximate {
+                factor: <O::Point as Point>::Coord::one() + epsilon,
+                candidates: BestMatch {
+                    distance_2: <O::Point as Point>::Coord::infinity(),
+                    best_match: None,
+                },
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        args.candidates.candidates.best_match
+    }
+
+    /// Find an object approximately nearest to the given `target` and verify it against the exact result
+    ///
+    /// Returns the result of [`nearest_approx`][Self::nearest_approx] together with the ratio of its distance to the distance of the result of [`nearest`][Self::nearest].
+    /// The ratio is one if both are equally distant to the `target`, including if the tree is empty.
+    ///
+    /// This does roughly twice the work of an exact search and is meant for validating choices of `epsilon` on real data, not for production use.
+    pub fn nearest_approx_verified(
+        &self,
+        target: &O::Point,
+        epsilon: <O::Point as Point>::Coord,
+    ) -> (Option<&O>, <O::Point as Point>::Coord) {
+        let approx = self.nearest_approx(target, epsilon);
+        let exact = self.nearest(target);
+
+        let ratio = match (approx, exact) {
+            (Some(approx), Some(exact)) => {
+                let approx_distance_2 = target.distance_2(approx.position());
+                let exact_distance_2 = target.distance_2(exact.position());
+
+                if approx_distance_2 == exact_distance_2 {
+                    <O::Point as Point>::Coord::one()
+                } else {
+                    (approx_distance_2 / exact_distance_2).sqrt()
+                }
+            }
+            _ => <O::Point as Point>::Coord::one(),
+        };
+
+        (approx, ratio)
+    }
+
+    #[cfg(feature = "std")]
+    /// Find the `k` objects nearest to the given `target`
+    ///
+    /// The result is sorted by ascending distance to the `target` and contains fewer than `k` objects if the tree does not contain `k` objects with a finite distance.
+    pub fn nearest_k(&self, target: &O::Point, k: usize) -> Vec<&O> {
+        let objects = self.objects.as_ref();
+
+        if k == 0 || objects.is_empty() {
+            return Vec::new();
+        }
+
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: KNearest {
+                k,
+                heap: BinaryHeap::with_capacity(k),
+            },
+        };
+
+        let _ = nearest(&mut args, objects, 0);
+
+        args.candidates
+            .heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|KNearestEntry(_distance_2, object)| object)
+            .collect()
+    }
+
+    #[cfg(feature = "std")]
+    /// Find the object nearest to each of the given `targets`
+    ///
+    /// The results are in the same order as the `targets`.
+    pub fn nearest_each(&self, targets: &[O::Point]) -> Vec<Option<&O>> {
+        targets.iter().map(|target| self.nearest(target)).collect()
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Find the object nearest to each of the given `targets`, in parallel
+    ///
+    /// The results are in the same order as the `targets` which are distributed across the thread pool.
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_nearest_each(&self, targets: &[O::Point]) -> Vec<Option<&O>>
+    where
+        O: Sync,
+        O::Point: Sync,
+    {
+        let objects = self.objects.as_ref();
+
+        targets
+            .par_iter()
+            .map(|target| nearest_slice(objects, target))
+            .collect()
+    }
+
+    /// Find the object nearest to the given `target`, starting from the given `hint`
+    ///
+    /// The search is seeded with the distance of the `hint` to the `target` so that subtrees farther away than the `hint` are pruned immediately.
+    /// This is beneficial if the `hint` is close to the actual result, e.g. the result of a previous search for a slightly different `target`.
+    ///
+    /// The result has the same distance to the `target` as the one of [`nearest`][Self::nearest], but the `hint` is preferred over other objects at the same distance.
+    pub fn nearest_hinted<'a>(&'a self, target: &O::Point, hint: Option<&'a O>) -> Option<&'a O> {
+        let distance_2 = match hint {
+            Some(hint) => target.distance_2(hint.position()),
+            None => <O::Point as Point>::Coord::infinity(),
+        };
+
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: BestMatch {
+                distance_2,
+                best_match: hint,
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        args.candidates.best_match
+    }
+
+    /// Find the object nearest to the given `target` computing at most `budget` distances
+    ///
+    /// The search is stopped once the distances of `budget` objects have been computed and the best match found so far is returned.
+    /// The second element of the result indicates whether the search completed within the `budget`.
+    ///
+    /// Note that the returned object is not necessarily the nearest one if the search did not complete.
+    pub fn nearest_budgeted(&self, target: &O::Point, budget: usize) -> (Option<&O>, bool) {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: Budgeted {
+                budget,
+                candidates: BestMatch {
+                    distance_2: <O::Point as Point>::Coord::infinity(),
+                    best_match: None,
+                },
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        let completed = objects.is_empty() || nearest(&mut args, objects, 0).is_continue();
+
+        (args.candidates.candidates.best_match, completed)
+    }
+
+    /// Find the object nearest to the given `target`, reporting each improvement of the best match found so far
+    ///
+    /// The `on_improve` callback is called with the new best match and its squared distance whenever a closer object is found during the search,
+    /// e.g. to display progressively refined results. The last call corresponds to the returned object.
+    pub fn nearest_progressive<'a, F>(&'a self, target: &O::Point, on_improve: F) -> Option<&'a O>
+    where
+        F: FnMut(&'a O, <O::Point as Point>::Coord),
+    {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: Progressive {
+                on_improve,
+                candidates: BestMatch {
+                    distance_2: <O::Point as Point>::Coord::infinity(),
+                    best_match: None,
+                },
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        args.candidates.candidates.best_match
+    }
+
+    /// Find the object nearest to the given `target`, breaking ties by the original order of the objects
+    ///
+    /// The `permutation` must be the one returned by [`new_with_permutation`][Self::new_with_permutation] when this tree was constructed.
+    /// Of all objects with minimal distance to the `target`, the one which came first in the sequence passed to the constructor is returned.
+    /// This makes the result reproducible independently of how equidistant objects are arranged by the construction.
+    ///
+    /// Note that keeping the permutation costs an additional `usize` per object and that equidistant subtrees can no longer be pruned.
+    pub fn nearest_stable(&self, target: &O::Point, permutation: &[usize]) -> Option<&O> {
+        let objects = self.objects.as_ref();
+
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: StableMatch {
+                objects,
+                permutation,
+                distance_2: <O::Point as Point>::Coord::infinity(),
+                best_match: None,
+            },
+        };
+
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, 0);
+        }
+
+        args.candidates.best_match.map(|(_index, object)| object)
+    }
+
+    /// Find the object nearest to the given `target` and return its index in the sequence originally passed to the constructor
+    ///
+    /// The `permutation` must be the one returned by [`new_with_permutation`][Self::new_with_permutation] when this tree was constructed.
+    /// Use [`nearest_stable`][Self::nearest_stable] instead if ties should be broken by the original order.
+    pub fn nearest_original_index(
+        &self,
+        target: &O::Point,
+        permutation: &[usize],
+    ) -> Option<usize> {
+        let objects = self.objects.as_ref();
+
+        self.nearest(target)
+            .map(|object| permutation[index_of(objects, object)])
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Find the object nearest to the given `target`, in parallel
+    ///
+    /// Subtrees which are large enough are searched concurrently, each starting from the best match found before splitting,
+    /// and their results are merged by keeping the closer one. The result has the same distance to the `target` as the one of [`nearest`][Self::nearest].
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_nearest(&self, target: &O::Point) -> Option<&O>
+    where
+        O: Sync,
+        O::Point: Sync,
+        <O::Point as Point>::Coord: Send,
+    {
+        let objects = self.objects.as_ref();
+
+        if objects.is_empty() {
+            return None;
+        }
+
+        let best_match = (<O::Point as Point>::Coord::infinity(), None);
+
+        par_nearest(target, objects, 0, best_match).1
+    }
+
+    #[cfg(feature = "std")]
+    /// Compute the squared distance of each object to its nearest neighbour
+    ///
+    /// The result contains the squared distance of each object to the nearest *other* object in the order of the objects in the tree.
+    /// Objects with identical positions are each other's nearest neighbours at distance zero.
+    ///
+    /// The distance is infinite if the tree contains no other object with a finite distance.
+    pub fn nearest_neighbour_distances(&self) -> Vec<<O::Point as Point>::Coord> {
+        let objects = self.objects.as_ref();
+
+        objects
+            .iter()
+            .map(|object| nearest_neighbour_distance(objects, object))
+            .collect()
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Compute the squared distance of each object to its nearest neighbour, in parallel
+    ///
+    /// The result is the same as for the [serial version][Self::nearest_neighbour_distances].
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_nearest_neighbour_distances(&self) -> Vec<<O::Point as Point>::Coord>
+    where
+        O: Sync,
+        <O::Point as Point>::Coord: Send,
+    {
+        let objects = self.objects.as_ref();
+
+        objects
+            .par_iter()
+            .map(|object| nearest_neighbour_distance(objects, object))
+            .collect()
+    }
+
+    #[cfg(feature = "std")]
+    /// Compute the squared distance of each object to its `k`-th nearest neighbour
+    ///
+    /// The result contains the squared distance of each object to its `k`-th nearest *other* object in the order of the objects in the tree,
+    /// e.g. as required to choose the radius for [DBSCAN](https://en.wikipedia.org/wiki/DBSCAN).
+    /// For `k` equal to one, this is the same as [`nearest_neighbour_distances`][Self::nearest_neighbour_distances].
+    ///
+    /// The distance is infinite if the tree contains fewer than `k` other objects with a finite distance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn k_distances(&self, k: usize) -> Vec<<O::Point as Point>::Coord> {
+        assert_ne!(k, 0);
+
+        let objects = self.objects.as_ref();
+
+        objects
+            .iter()
+            .map(|object| k_distance(objects, object, k))
+            .collect()
+    }
+
+    #[cfg(feature = "rayon")]
+    /// Compute the squared distance of each object to its `k`-th nearest neighbour, in parallel
+    ///
+    /// The result is the same as for the [serial version][Self::k_distances].
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero.
+    pub fn par_k_distances(&self, k: usize) -> Vec<<O::Point as Point>::Coord>
+    where
+        O: Sync,
+        <O::Point as Point>::Coord: Send,
+    {
+        assert_ne!(k, 0);
+
+        let objects = self.objects.as_ref();
+
+        objects
+            .par_iter()
+            .map(|object| k_distance(objects, object, k))
+            .collect()
+    }
+}
+
+impl<O, S, T, const N: usize> KdTree<O, S>
+where
+    O: Object<Point = [T; N]>,
+    T: Float,
+    S: AsRef<[O]>,
+{
+    #[cfg(feature = "std")]
+    /// Find the object nearest to the center of each cell of a grid which is contained in that cell
+    ///
+    /// The grid starts at `origin` and has `dims` cells of size `cell_size` along each axis.
+    /// Each cell contains the positions from its lower corner inclusive to its upper corner exclusive.
+    /// The cells are enumerated in row-major order, i.e. the index along the last axis varies fastest, and the result contains `None` for cells without any object.
+    ///
+    /// This performs a bounded nearest neighbour search per cell and is hence best suited for grids which are coarse compared to the density of the objects.
+    pub fn grid_representatives(
+        &self,
+        origin: [T; N],
+        cell_size: [T; N],
+        dims: [usize; N],
+    ) -> Vec<Option<&O>> {
+        let objects = self.objects.as_ref();
+
+        let len = dims.iter().product::<usize>();
+
+        // The squared half-diagonal bounds the squared distance of any position within a cell to its center.
+        let max_distance_2 = cell_size.iter().fold(T::zero(), |sum, size| {
+            let half_size = *size / (T::one() + T::one());
+
+            sum + half_size * half_size
+        });
+
+        let mut results = Vec::with_capacity(len);
+
+        for cell in 0..len {
+            let mut index = cell;
+            let mut lower = origin;
+            let mut upper = origin;
+
+            for axis in (0..N).rev() {
+                let offset = T::from(index % dims[axis]).unwrap();
+                index /= dims[axis];
+
+                lower[axis] = origin[axis] + offset * cell_size[axis];
+                upper[axis] = lower[axis] + cell_size[axis];
+            }
+
+            let mut center = lower;
+
+            for (center, upper) in center.iter_mut().zip(&upper) {
+                *center = (*center + *upper) / (T::one() + T::one());
+            }
+
+            let mut args = NearestArgs {
+                target: &center,
+                metric: &Euclidean,
+                candidates: Within {
+                    max_distance_2,
+                    candidates: Matching {
+                        predicate: |object: &O| {
+                            let position = object.position();
+
+                            (0..N).all(|axis| {
+                                lower[axis] <= position[axis] && position[axis] < upper[axis]
+                            })
+                        },
+                        candidates: BestMatch {
+                            distance_2: T::infinity(),
+                            best_match: None,
+                        },
+                    },
+                },
+            };
+
+            if !objects.is_empty() {
+                let _ = nearest(&mut args, objects, 0);
+            }
+
+            results.push(args.candidates.candidates.candidates.best_match);
+        }
+
+        results
+    }
+
+    /// Find the object nearest to the given `target` within those subtrees whose regions are kept by the given predicate
+    ///
+    /// The region of a subtree is the AABB of the space it covers, starting from the whole space for the tree itself and being split at each node along its axis.
+    /// Subtrees for which `keep_subtree` returns `false` are fully excluded from the result, including their root objects, even if they are closer to the `target` than any other object.
+    pub fn nearest_in_regions<F>(&self, target: &[T; N], keep_subtree: F) -> Option<&O>
+    where
+        F: Fn(&([T; N], [T; N])) -> bool,
+    {
         let mut args = NearestArgs {
             target,
+            metric: &Euclidean,
+            candidates: BestMatch {
+                distance_2: T::infinity(),
+                best_match: None,
+            },
+        };
+
+        let objects = self.objects.as_ref();
+
+        if !objects.is_empty() {
+            let region = ([T::neg_infinity(); N], [T::infinity(); N]);
+
+            let _ = nearest_in_regions(&mut args, &keep_subtree, objects, 0, region);
+        }
+
+        args.candidates.best_match
+    }
+}
+
+/// Find the object nearest to the given `target` in the given `objects`
+///
+/// This is equivalent to [`KdTree::nearest`] but operates directly on a slice of objects without wrapping it into a [`KdTree`].
+///
+/// The `objects` must already be sorted as a tree, for example by [`KdTree::new`].
+/// Supplying `objects` which are not actually sorted is safe but will lead to incorrect results.
+///
+/// This function does not allocate and hence can be used to query trees in borrowed storage without access to a heap.
+pub fn nearest_slice<'a, O>(objects: &'a [O], target: &O::Point) -> Option<&'a O>
+where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+{
+    nearest_slice_by_metric(objects, target, &Euclidean, 1)
+}
+
+/// Works like [`nearest_slice`] but uses the given `metric` and scans subtrees of up to `leaf_size` objects linearly
+pub(crate) fn nearest_slice_by_metric<'a, O, M>(
+    objects: &'a [O],
+    target: &O::Point,
+    metric: &M,
+    leaf_size: usize,
+) -> Option<&'a O>
+where
+    O: Object,
+    M: Metric<O::Point>,
+    <O::Point as Point>::Coord: Float,
+{
+    let mut args = NearestArgs {
+        target,
+        metric,
+        candidates: BestMatch {
             distance_2: <O::Point as Point>::Coord::infinity(),
             best_match: None,
+        },
+    };
+
+    if objects.len() <= leaf_size.max(2) {
+        // Tiny trees are scanned directly in the order of traversal, i.e. the root before its left child.
+        for object in objects.iter().rev() {
+            let _ = args
+                .candidates
+                .insert(object, metric.distance(target, object.position()));
+        }
+    } else {
+        let _ = nearest_buckets(&mut args, objects, 0, leaf_size);
+    }
+
+    args.candidates.best_match
+}
+
+#[cfg(feature = "rayon")]
+fn par_nearest<'a, O>(
+    target: &O::Point,
+    objects: &'a [O],
+    axis: usize,
+    best_match: (<O::Point as Point>::Coord, Option<&'a O>),
+) -> (<O::Point as Point>::Coord, Option<&'a O>)
+where
+    O: Object + Sync,
+    O::Point: Distance + Sync,
+    <O::Point as Point>::Coord: Float + Send,
+{
+    const SEQUENTIAL_LEN: usize = 32;
+
+    if objects.len() <= SEQUENTIAL_LEN {
+        let mut args = NearestArgs {
+            target,
+            metric: &Euclidean,
+            candidates: BestMatch {
+                distance_2: best_match.0,
+                best_match: best_match.1,
+            },
         };
 
-        let objects = self.objects.as_ref();
+        if !objects.is_empty() {
+            let _ = nearest(&mut args, objects, axis);
+        }
+
+        return (args.candidates.distance_2, args.candidates.best_match);
+    }
+
+    let (mut left, object, mut right) = split(objects);
+
+    let position = object.position();
+
+    let distance_2 = target.distance_2(position);
+
+    let best_match = if best_match.0 > distance_2 {
+        (distance_2, Some(object))
+    } else {
+        best_match
+    };
+
+    let offset = target.coord(axis) - position.coord(axis);
+    let axis_distance_2 = target.axis_distance_2(axis, position.coord(axis));
+
+    if offset.is_sign_positive() {
+        swap(&mut left, &mut right);
+    }
+
+    let axis = (axis + 1) % O::Point::DIM;
+
+    let (left, right) = join(
+        move || par_nearest(target, left, axis, best_match),
+        move || {
+            if best_match.0 > axis_distance_2 {
+                par_nearest(target, right, axis, best_match)
+            } else {
+                best_match
+            }
+        },
+    );
+
+    if left.0 > right.0 {
+        right
+    } else {
+        left
+    }
+}
+
+#[cfg(feature = "std")]
+fn nearest_neighbour_distance<O>(objects: &[O], object: &O) -> <O::Point as Point>::Coord
+where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+{
+    let mut args = NearestArgs {
+        target: object.position(),
+        metric: &Euclidean,
+        candidates: Excluding {
+            excluded: object,
+            candidates: BestMatch {
+                distance_2: <O::Point as Point>::Coord::infinity(),
+                best_match: None,
+            },
+        },
+    };
+
+    let _ = nearest(&mut args, objects, 0);
+
+    args.candidates.candidates.distance_2
+}
+
+#[cfg(feature = "std")]
+fn k_distance<O>(objects: &[O], object: &O, k: usize) -> <O::Point as Point>::Coord
+where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+{
+    let mut args = NearestArgs {
+        target: object.position(),
+        metric: &Euclidean,
+        candidates: Excluding {
+            excluded: object,
+            candidates: KNearest {
+                k,
+                heap: BinaryHeap::with_capacity(k),
+            },
+        },
+    };
+
+    let _ = nearest(&mut args, objects, 0);
+
+    let heap = args.candidates.candidates.heap;
+
+    match heap.peek() {
+        Some(KNearestEntry(distance_2, _)) if heap.len() == k => *distance_2,
+        _ => <O::Point as Point>::Coord::infinity(),
+    }
+}
+
+struct NearestArgs<'b, P, M, C> {
+    target: &'b P,
+    metric: &'b M,
+    candidates: C,
+}
+
+trait Candidates<'a, O>
+where
+    O: Object,
+{
+    /// Check whether objects at the given squared distance could still improve the result
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool;
+
+    /// Consider the given `object` at the given squared distance for inclusion in the result
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()>;
+
+    /// Called before the distance of the next object is computed so that the search can be stopped without computing it
+    fn evaluate(&mut self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+struct BestMatch<'a, O>
+where
+    O: Object,
+{
+    distance_2: <O::Point as Point>::Coord,
+    best_match: Option<&'a O>,
+}
+
+impl<'a, O> Candidates<'a, O> for BestMatch<'a, O>
+where
+    O: Object,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.distance_2 > distance_2
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if self.distance_2 > distance_2 {
+            self.distance_2 = distance_2;
+            self.best_match = Some(object);
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(feature = "std")]
+struct KNearest<'a, O>
+where
+    O: Object,
+{
+    k: usize,
+    heap: BinaryHeap<KNearestEntry<'a, O>>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, O> Candidates<'a, O> for KNearest<'a, O>
+where
+    O: Object,
+    <O::Point as Point>::Coord: Float,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        match self.heap.peek() {
+            Some(KNearestEntry(max_distance_2, _)) if self.heap.len() == self.k => {
+                *max_distance_2 > distance_2
+            }
+            _ => true,
+        }
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if distance_2 < <O::Point as Point>::Coord::infinity() && self.admits(distance_2) {
+            if self.heap.len() == self.k {
+                self.heap.pop();
+            }
+
+            self.heap.push(KNearestEntry(distance_2, object));
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(feature = "std")]
+struct KNearestEntry<'a, O>(<O::Point as Point>::Coord, &'a O)
+where
+    O: Object;
+
+#[cfg(feature = "std")]
+impl<O> PartialEq for KNearestEntry<'_, O>
+where
+    O: Object,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+#[cfg(feature = "std")]
+impl<O> Eq for KNearestEntry<'_, O> where O: Object {}
+
+#[cfg(feature = "std")]
+impl<O> PartialOrd for KNearestEntry<'_, O>
+where
+    O: Object,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<O> Ord for KNearestEntry<'_, O>
+where
+    O: Object,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).unwrap()
+    }
+}
+
+struct StableMatch<'a, 'b, O>
+where
+    O: Object,
+{
+    objects: &'a [O],
+    permutation: &'b [usize],
+    distance_2: <O::Point as Point>::Coord,
+    best_match: Option<(usize, &'a O)>,
+}
+
+impl<'a, O> Candidates<'a, O> for StableMatch<'a, '_, O>
+where
+    O: Object,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.distance_2 >= distance_2
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        let index = self.permutation[index_of(self.objects, object)];
+
+        let better = match self.best_match {
+            Some((best_index, _)) => {
+                self.distance_2 > distance_2
+                    || (self.distance_2 == distance_2 && best_index > index)
+            }
+            None => self.distance_2 > distance_2,
+        };
+
+        if better {
+            self.distance_2 = distance_2;
+            self.best_match = Some((index, object));
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(feature = "std")]
+struct Excluding<'a, O, C> {
+    excluded: &'a O,
+    candidates: C,
+}
+
+#[cfg(feature = "std")]
+impl<'a, O, C> Candidates<'a, O> for Excluding<'a, O, C>
+where
+    O: Object,
+    C: Candidates<'a, O>,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.candidates.admits(distance_2)
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if !ptr::eq(self.excluded, object) {
+            self.candidates.insert(object, distance_2)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn evaluate(&mut self) -> ControlFlow<()> {
+        self.candidates.evaluate()
+    }
+}
+
+struct Within<T, C> {
+    max_distance_2: T,
+    candidates: C,
+}
+
+impl<'a, O, C> Candidates<'a, O> for Within<<O::Point as Point>::Coord, C>
+where
+    O: Object,
+    C: Candidates<'a, O>,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.max_distance_2 >= distance_2 && self.candidates.admits(distance_2)
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if self.max_distance_2 >= distance_2 {
+            self.candidates.insert(object, distance_2)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn evaluate(&mut self) -> ControlFlow<()> {
+        self.candidates.evaluate()
+    }
+}
+
+struct Matching<F, C> {
+    predicate: F,
+    candidates: C,
+}
+
+impl<'a, O, F, C> Candidates<'a, O> for Matching<F, C>
+where
+    O: Object,
+    F: Fn(&O) -> bool,
+    C: Candidates<'a, O>,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.candidates.admits(distance_2)
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if (self.predicate)(object) {
+            self.candidates.insert(object, distance_2)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn evaluate(&mut self) -> ControlFlow<()> {
+        self.candidates.evaluate()
+    }
+}
+
+#[cfg(feature = "std")]
+struct Ties<'a, O>
+where
+    O: Object,
+{
+    factor: <O::Point as Point>::Coord,
+    distance_2: <O::Point as Point>::Coord,
+    ties: Vec<(<O::Point as Point>::Coord, &'a O)>,
+}
+
+#[cfg(feature = "std")]
+impl<'a, O> Candidates<'a, O> for Ties<'a, O>
+where
+    O: Object,
+    <O::Point as Point>::Coord: Float,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        // Like `BestMatch`, objects at infinite distance are never considered nearest.
+        distance_2.is_finite() && self.distance_2 * self.factor >= distance_2
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if self.distance_2 > distance_2 {
+            self.distance_2 = distance_2;
+
+            let max_distance_2 = distance_2 * self.factor;
+
+            self.ties
+                .retain(|(distance_2, _object)| max_distance_2 >= *distance_2);
+        }
+
+        if self.admits(distance_2) {
+            self.ties.push((distance_2, object));
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+struct Approximate<T, C> {
+    factor: T,
+    candidates: C,
+}
+
+impl<'a, O, C> Candidates<'a, O> for Approximate<<O::Point as Point>::Coord, C>
+where
+    O: Object,
+    C: Candidates<'a, O>,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.candidates.admits(distance_2 * self.factor)
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        self.candidates.insert(object, distance_2)
+    }
+
+    fn evaluate(&mut self) -> ControlFlow<()> {
+        self.candidates.evaluate()
+    }
+}
+
+struct Budgeted<C> {
+    budget: usize,
+    candidates: C,
+}
+
+impl<'a, O, C> Candidates<'a, O> for Budgeted<C>
+where
+    O: Object,
+    C: Candidates<'a, O>,
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.candidates.admits(distance_2)
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        self.candidates.insert(object, distance_2)
+    }
+
+    fn evaluate(&mut self) -> ControlFlow<()> {
+        if self.budget == 0 {
+            return ControlFlow::Break(());
+        }
+
+        self.budget -= 1;
+
+        self.candidates.evaluate()
+    }
+}
+
+struct Progressive<'a, O, F>
+where
+    O: Object,
+{
+    on_improve: F,
+    candidates: BestMatch<'a, O>,
+}
+
+impl<'a, O, F> Candidates<'a, O> for Progressive<'a, O, F>
+where
+    O: Object,
+    F: FnMut(&'a O, <O::Point as Point>::Coord),
+{
+    fn admits(&self, distance_2: <O::Point as Point>::Coord) -> bool {
+        self.candidates.admits(distance_2)
+    }
+
+    fn insert(&mut self, object: &'a O, distance_2: <O::Point as Point>::Coord) -> ControlFlow<()> {
+        if self.candidates.admits(distance_2) {
+            self.candidates.insert(object, distance_2)?;
+
+            (self.on_improve)(object, distance_2);
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+fn nearest<'a, O, M, C>(
+    args: &mut NearestArgs<'_, O::Point, M, C>,
+    objects: &'a [O],
+    axis: usize,
+) -> ControlFlow<()>
+where
+    O: Object,
+    M: Metric<O::Point>,
+    <O::Point as Point>::Coord: Float,
+    C: Candidates<'a, O>,
+{
+    nearest_buckets(args, objects, axis, 1)
+}
+
+/// Works like [`nearest`] but scans subtrees of up to `leaf_size` objects linearly instead of splitting them
+fn nearest_buckets<'a, O, M, C>(
+    args: &mut NearestArgs<'_, O::Point, M, C>,
+    mut objects: &'a [O],
+    mut axis: usize,
+    leaf_size: usize,
+) -> ControlFlow<()>
+where
+    O: Object,
+    M: Metric<O::Point>,
+    <O::Point as Point>::Coord: Float,
+    C: Candidates<'a, O>,
+{
+    loop {
+        if objects.len() <= leaf_size {
+            // Buckets are not sorted and hence scanned linearly.
+            for object in objects {
+                args.candidates.evaluate()?;
+
+                let distance_2 = args.metric.distance(args.target, object.position());
+
+                args.candidates.insert(object, distance_2)?;
+            }
+
+            return ControlFlow::Continue(());
+        }
+
+        let (mut left, object, mut right) = split(objects);
+
+        let position = object.position();
+
+        args.candidates.evaluate()?;
+
+        let distance_2 = args.metric.distance(args.target, position);
+
+        args.candidates.insert(object, distance_2)?;
+
+        let offset = args.target.coord(axis) - position.coord(axis);
+        let axis_distance_2 = args
+            .metric
+            .axis_distance(args.target, axis, position.coord(axis));
+
+        if offset.is_sign_positive() {
+            swap(&mut left, &mut right);
+        }
+
+        let search_left = !left.is_empty();
+        let search_right = !right.is_empty();
+
+        axis = (axis + 1) % O::Point::DIM;
+
+        if search_right {
+            if search_left {
+                nearest_buckets(args, left, axis, leaf_size)?;
+            }
+
+            if args.candidates.admits(axis_distance_2) {
+                objects = right;
+            } else {
+                return ControlFlow::Continue(());
+            }
+        } else if search_left {
+            objects = left;
+        } else {
+            return ControlFlow::Continue(());
+        }
+    }
+}
+
+fn nearest_in_regions<'a, O, T, M, C, F, const N: usize>(
+    args: &mut NearestArgs<'_, [T; N], M, C>,
+    keep_subtree: &F,
+    objects: &'a [O],
+    axis: usize,
+    region: ([T; N], [T; N]),
+) -> ControlFlow<()>
+where
+    O: Object<Point = [T; N]>,
+    T: Float,
+    M: Metric<[T; N]>,
+    C: Candidates<'a, O>,
+    F: Fn(&([T; N], [T; N])) -> bool,
+{
+    if !keep_subtree(&region) {
+        return ControlFlow::Continue(());
+    }
+
+    let (mut left, object, mut right) = split(objects);
+
+    let position = object.position();
+
+    args.candidates.evaluate()?;
+
+    let distance_2 = args.metric.distance(args.target, position);
+
+    args.candidates.insert(object, distance_2)?;
+
+    let mut left_region = region;
+    left_region.1[axis] = position[axis];
+
+    let mut right_region = region;
+    right_region.0[axis] = position[axis];
+
+    let offset = args.target[axis] - position[axis];
+    let axis_distance_2 = args.metric.axis_distance(args.target, axis, position[axis]);
+
+    if offset.is_sign_positive() {
+        swap(&mut left, &mut right);
+        swap(&mut left_region, &mut right_region);
+    }
+
+    let next_axis = (axis + 1) % N;
+
+    if !left.is_empty() {
+        nearest_in_regions(args, keep_subtree, left, next_axis, left_region)?;
+    }
+
+    if !right.is_empty() && args.candidates.admits(axis_distance_2) {
+        nearest_in_regions(args, keep_subtree, right, next_axis, right_region)?;
+    }
+
+    ControlFlow::Continue(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    use crate::tests::{
+        random_objects, random_objects_1d, random_points, random_points_1d, RandomObject,
+    };
+
+    #[test]
+    fn random_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let result1 = index
+                            .iter()
+                            .min_by(|lhs, rhs| {
+                                let lhs = lhs.0.distance_2(&target);
+                                let rhs = rhs.0.distance_2(&target);
+
+                                lhs.partial_cmp(&rhs).unwrap()
+                            })
+                            .unwrap();
+
+                        let result2 = index.nearest(&target).unwrap();
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn random_par_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::par_new(objects);
+
+                    for target in targets {
+                        let result1 = index.nearest(&target);
+
+                        let result2 = index.par_nearest(&target);
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_each() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::new(objects);
+
+                    let results1 = targets
+                        .iter()
+                        .map(|target| index.nearest(target))
+                        .collect::<Vec<_>>();
+
+                    let results2 = index.nearest_each(&targets);
+                    assert_eq!(results1, results2);
+
+                    #[cfg(feature = "rayon")]
+                    {
+                        let results3 = index.par_nearest_each(&targets);
+                        assert_eq!(results1, results3);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_1d() {
+        TestRunner::default()
+            .run(
+                &(random_objects_1d(100), random_points_1d(10)),
+                |(objects, targets)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let result1 = index
+                            .iter()
+                            .map(|object| object.0.distance_2(&target))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                            .unwrap();
+
+                        let result2 = index.nearest(&target).unwrap().0.distance_2(&target);
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_tiny() {
+        for len in 0..=3 {
+            TestRunner::default()
+                .run(
+                    &(random_objects(len), random_points(10)),
+                    |(objects, targets)| {
+                        let index = KdTree::new(objects);
+
+                        for target in targets {
+                            let result1 = index.iter().min_by(|lhs, rhs| {
+                                let lhs = lhs.0.distance_2(&target);
+                                let rhs = rhs.0.distance_2(&target);
+
+                                lhs.partial_cmp(&rhs).unwrap()
+                            });
+
+                            let result2 = index.nearest(&target);
+
+                            assert_eq!(result1, result2);
+                        }
+
+                        Ok(())
+                    },
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn random_nearest_within() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=0.2),
+                |(objects, targets, max_distance)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let result1 = index.nearest(&target).filter(|object| {
+                            object.0.distance_2(&target) <= max_distance * max_distance
+                        });
+
+                        let result2 = index.nearest_within(&target, max_distance);
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_matching() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::new(objects);
+
+                    let predicate = |object: &RandomObject| object.0[0] + object.0[1] > 1.0;
+
+                    for target in targets {
+                        let result1 =
+                            index
+                                .iter()
+                                .filter(|object| predicate(object))
+                                .min_by(|lhs, rhs| {
+                                    let lhs = lhs.0.distance_2(&target);
+                                    let rhs = rhs.0.distance_2(&target);
+
+                                    lhs.partial_cmp(&rhs).unwrap()
+                                });
+
+                        let result2 = index.nearest_matching(&target, predicate);
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_excluding_regions() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), random_points(6)),
+                |(objects, targets, corners)| {
+                    let index = KdTree::new(objects);
+
+                    let blocked = corners
+                        .chunks(2)
+                        .map(|corners| {
+                            (
+                                [
+                                    corners[0][0].min(corners[1][0]),
+                                    corners[0][1].min(corners[1][1]),
+                                ],
+                                [
+                                    corners[0][0].max(corners[1][0]),
+                                    corners[0][1].max(corners[1][1]),
+                                ],
+                            )
+                        })
+                        .collect::<Vec<_>>();
+
+                    for target in targets {
+                        let result1 = index
+                            .iter()
+                            .filter(|object| !blocked.iter().any(|aabb| contains(aabb, &object.0)))
+                            .min_by(|lhs, rhs| {
+                                let lhs = lhs.0.distance_2(&target);
+                                let rhs = rhs.0.distance_2(&target);
+
+                                lhs.partial_cmp(&rhs).unwrap()
+                            });
+
+                        let result2 = index.nearest_excluding_regions(&target, &blocked);
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_ties_eps() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, targets, relative_eps)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let min_distance_2 = index
+                            .iter()
+                            .map(|object| object.0.distance_2(&target))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                            .unwrap();
+
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| {
+                                object.0.distance_2(&target)
+                                    <= min_distance_2 * (1.0 + relative_eps)
+                            })
+                            .collect::<Vec<_>>();
 
-        if !objects.is_empty() {
-            nearest(&mut args, objects, 0);
-        }
+                        let mut results2 = index.nearest_ties_eps(&target, relative_eps);
+
+                        assert!(results2.windows(2).all(|pair| {
+                            pair[0].0.distance_2(&target) <= pair[1].0.distance_2(&target)
+                        }));
 
-        args.best_match
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
     }
-}
 
-struct NearestArgs<'a, 'b, O>
-where
-    O: Object,
-{
-    target: &'b O::Point,
-    distance_2: <O::Point as Point>::Coord,
-    best_match: Option<&'a O>,
-}
+    #[test]
+    fn nearest_ties_eps_ignores_infinite_distances() {
+        let index = KdTree::new(
+            [
+                [f32::INFINITY, 0.0],
+                [0.0, f32::NEG_INFINITY],
+                [f32::NEG_INFINITY, f32::INFINITY],
+            ]
+            .map(RandomObject),
+        );
 
-fn nearest<'a, O>(args: &mut NearestArgs<'a, '_, O>, mut objects: &'a [O], mut axis: usize)
-where
-    O: Object,
-    O::Point: Distance,
-    <O::Point as Point>::Coord: Float,
-{
-    loop {
-        let (mut left, object, mut right) = split(objects);
+        let target = [0.0, 0.0];
 
-        let position = object.position();
+        assert_eq!(index.nearest(&target), None);
+        assert_eq!(
+            index.nearest_ties_eps(&target, 0.0),
+            Vec::<&RandomObject>::new()
+        );
+        assert_eq!(
+            index.nearest_ties_eps(&target, 1.0),
+            Vec::<&RandomObject>::new()
+        );
+    }
+
+    #[test]
+    fn random_nearest_approx() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, targets, epsilon)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let result1 = index.nearest(&target).unwrap();
+
+                        let result2 = index.nearest_approx(&target, 0.0).unwrap();
+                        assert_eq!(result1, result2);
+
+                        let result3 = index.nearest_approx(&target, epsilon).unwrap();
+                        assert!(
+                            result3.0.distance_2(&target)
+                                <= result1.0.distance_2(&target) * (1.0 + epsilon)
+                        );
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_approx_verified() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, targets, epsilon)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let (result, ratio) = index.nearest_approx_verified(&target, epsilon);
+
+                        assert_eq!(result, index.nearest_approx(&target, epsilon));
+                        assert!(ratio >= 1.0);
+                        assert!(ratio * ratio <= (1.0 + epsilon) * (1.0 + 1e-6));
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_k() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0_usize..120),
+                |(objects, targets, k)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let mut distances1 = index
+                            .iter()
+                            .map(|object| object.0.distance_2(&target))
+                            .collect::<Vec<_>>();
+                        distances1.sort_unstable_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+                        distances1.truncate(k);
+
+                        let distances2 = index
+                            .nearest_k(&target, k)
+                            .into_iter()
+                            .map(|object| object.0.distance_2(&target))
+                            .collect::<Vec<_>>();
+
+                        assert_eq!(distances1, distances2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_hinted() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    random_points(10),
+                    vec(0..100_usize, 10),
+                ),
+                |(objects, targets, hints)| {
+                    let index = KdTree::new(objects);
+
+                    for (target, hint) in targets.into_iter().zip(hints) {
+                        let result1 = index.nearest(&target).unwrap();
+
+                        let result2 = index.nearest_hinted(&target, Some(&index[hint])).unwrap();
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
 
-        let distance_2 = args.target.distance_2(position);
+    #[test]
+    fn random_nearest_budgeted() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::new(objects);
+
+                    for target in targets {
+                        let result1 = index.nearest(&target);
+
+                        let (result2, completed) = index.nearest_budgeted(&target, 100);
+                        assert!(completed);
+                        assert_eq!(result1, result2);
+
+                        let (result3, _completed) = index.nearest_budgeted(&target, 5);
+                        assert!(result3.is_some());
+
+                        let (result4, completed) = index.nearest_budgeted(&target, 0);
+                        assert!(!completed);
+                        assert_eq!(result4, None);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
 
-        if args.distance_2 > distance_2 {
-            args.distance_2 = distance_2;
-            args.best_match = Some(object);
+    #[test]
+    fn nearest_budgeted_computes_at_most_budget_distances() {
+        thread_local! {
+            static DISTANCES: Cell<usize> = const { Cell::new(0) };
         }
 
-        let offset = args.target.coord(axis) - position.coord(axis);
+        #[derive(Debug, PartialEq)]
+        struct Counted([f32; 2]);
 
-        if offset.is_sign_positive() {
-            swap(&mut left, &mut right);
+        impl Point for Counted {
+            const DIM: usize = 2;
+
+            type Coord = f32;
+
+            fn coord(&self, axis: usize) -> Self::Coord {
+                self.0[axis]
+            }
         }
 
-        let search_left = !left.is_empty();
-        let search_right = !right.is_empty();
+        impl Distance for Counted {
+            fn distance_2(&self, other: &Self) -> Self::Coord {
+                DISTANCES.with(|distances| distances.set(distances.get() + 1));
 
-        axis = (axis + 1) % O::Point::DIM;
+                self.0.distance_2(&other.0)
+            }
+        }
 
-        if search_right {
-            if search_left {
-                nearest(args, left, axis);
+        impl Object for Counted {
+            type Point = Self;
+
+            fn position(&self) -> &Self::Point {
+                self
             }
+        }
 
-            if args.distance_2 > offset.powi(2) {
-                objects = right;
-            } else {
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_points(10), 0_usize..=100),
+                |(points, targets, budget)| {
+                    let index = KdTree::new(points.into_iter().map(Counted).collect::<Box<[_]>>());
+
+                    for target in targets {
+                        DISTANCES.with(|distances| distances.set(0));
+
+                        let (_result, completed) = index.nearest_budgeted(&Counted(target), budget);
+
+                        let distances = DISTANCES.with(Cell::get);
+
+                        assert!(distances <= budget);
+
+                        if !completed {
+                            assert_eq!(distances, budget);
+                        }
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_in_regions() {
+        fn kept<'a>(
+            objects: &'a [RandomObject],
+            axis: usize,
+            region: ([f32; 2], [f32; 2]),
+            keep_subtree: &impl Fn(&([f32; 2], [f32; 2])) -> bool,
+            results: &mut Vec<&'a RandomObject>,
+        ) {
+            if objects.is_empty() || !keep_subtree(&region) {
                 return;
             }
-        } else if search_left {
-            objects = left;
-        } else {
-            return;
+
+            let (left, object, right) = split(objects);
+
+            results.push(object);
+
+            let mut left_region = region;
+            left_region.1[axis] = object.0[axis];
+
+            let mut right_region = region;
+            right_region.0[axis] = object.0[axis];
+
+            kept(left, (axis + 1) % 2, left_region, keep_subtree, results);
+            kept(right, (axis + 1) % 2, right_region, keep_subtree, results);
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, targets, threshold)| {
+                    let index = KdTree::new(objects);
+
+                    let keep_subtree = |region: &([f32; 2], [f32; 2])| region.0[0] <= threshold;
+
+                    let mut objects = Vec::new();
+                    kept(
+                        &index,
+                        0,
+                        ([f32::NEG_INFINITY; 2], [f32::INFINITY; 2]),
+                        &keep_subtree,
+                        &mut objects,
+                    );
+
+                    for target in targets {
+                        let result1 = objects
+                            .iter()
+                            .map(|object| object.0.distance_2(&target))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+                        let result2 = index
+                            .nearest_in_regions(&target, keep_subtree)
+                            .map(|object| object.0.distance_2(&target));
+
+                        assert_eq!(result1, result2);
+
+                        let result3 = index.nearest_in_regions(&target, |_| true);
 
-    use proptest::test_runner::TestRunner;
+                        assert_eq!(index.nearest(&target), result3);
+                    }
 
-    use crate::tests::{random_objects, random_points};
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
 
     #[test]
-    fn random_nearest() {
+    fn random_nearest_progressive() {
         TestRunner::default()
             .run(
                 &(random_objects(100), random_points(10)),
@@ -106,17 +1873,155 @@ mod tests {
                     let index = KdTree::new(objects);
 
                     for target in targets {
-                        let result1 = index
+                        let mut improvements = Vec::new();
+
+                        let result = index.nearest_progressive(&target, |object, distance_2| {
+                            improvements.push((object, distance_2));
+                        });
+
+                        assert!(improvements
+                            .windows(2)
+                            .all(|improvements| improvements[0].1 > improvements[1].1));
+
+                        let (last_object, last_distance_2) = *improvements.last().unwrap();
+
+                        assert_eq!(result, Some(last_object));
+                        assert_eq!(last_distance_2, last_object.0.distance_2(&target));
+                        assert_eq!(index.nearest(&target), result);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_grid_representatives() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index = KdTree::new(objects);
+
+                let results = index.grid_representatives([0.0, 0.0], [0.25, 0.5], [4, 2]);
+
+                assert_eq!(results.len(), 8);
+
+                for (cell, result) in results.into_iter().enumerate() {
+                    let (x, y) = ((cell / 2) as f32 * 0.25, (cell % 2) as f32 * 0.5);
+                    let center = [x + 0.125, y + 0.25];
+
+                    let expected = index
+                        .iter()
+                        .filter(|object| {
+                            x <= object.0[0]
+                                && object.0[0] < x + 0.25
+                                && y <= object.0[1]
+                                && object.0[1] < y + 0.5
+                        })
+                        .min_by(|lhs, rhs| {
+                            let lhs = lhs.0.distance_2(&center);
+                            let rhs = rhs.0.distance_2(&center);
+
+                            lhs.partial_cmp(&rhs).unwrap()
+                        });
+
+                    assert_eq!(expected, result);
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_neighbour_distances() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index = KdTree::new(objects);
+
+                let distances = index.nearest_neighbour_distances();
+
+                #[cfg(feature = "rayon")]
+                assert_eq!(index.par_nearest_neighbour_distances(), distances);
+
+                for (object, distance_2) in index.iter().zip(distances) {
+                    let result = index
+                        .iter()
+                        .filter(|other| !ptr::eq(*other, object))
+                        .map(|other| other.0.distance_2(&object.0))
+                        .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                        .unwrap();
+
+                    assert_eq!(result, distance_2);
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_k_distances() {
+        TestRunner::default()
+            .run(&(random_objects(100), 1_usize..120), |(objects, k)| {
+                let index = KdTree::new(objects);
+
+                let distances = index.k_distances(k);
+
+                #[cfg(feature = "rayon")]
+                assert_eq!(index.par_k_distances(k), distances);
+
+                for (object, distance_2) in index.iter().zip(distances) {
+                    let mut results = index
+                        .iter()
+                        .filter(|other| !ptr::eq(*other, object))
+                        .map(|other| other.0.distance_2(&object.0))
+                        .collect::<Vec<_>>();
+
+                    results.sort_unstable_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+                    let result = results.get(k - 1).copied().unwrap_or(f32::INFINITY);
+
+                    assert_eq!(result, distance_2);
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_nearest_stable() {
+        TestRunner::default()
+            .run(
+                &(vec((0..5, 0..5), 100), vec((0..5, 0..5), 10)),
+                |(points, targets)| {
+                    let points = points
+                        .into_iter()
+                        .map(|(x, y)| [x as f32, y as f32])
+                        .collect::<Vec<_>>();
+
+                    let (index, permutation) = KdTree::new_with_permutation(
+                        points
                             .iter()
-                            .min_by(|lhs, rhs| {
-                                let lhs = lhs.0.distance_2(&target);
-                                let rhs = rhs.0.distance_2(&target);
+                            .map(|point| RandomObject(*point))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    for (x, y) in targets {
+                        let target = [x as f32, y as f32];
+
+                        let result1 = (0..points.len())
+                            .min_by(|&lhs, &rhs| {
+                                let lhs = points[lhs].distance_2(&target);
+                                let rhs = points[rhs].distance_2(&target);
 
                                 lhs.partial_cmp(&rhs).unwrap()
                             })
                             .unwrap();
 
-                        let result2 = index.nearest(&target).unwrap();
+                        let result2 = index.nearest_stable(&target, &permutation).unwrap();
+                        let result2 = permutation[index_of(&index, result2)];
 
                         assert_eq!(result1, result2);
                     }
@@ -126,4 +2031,35 @@ mod tests {
             )
             .unwrap();
     }
+
+    #[test]
+    fn random_nearest_original_index() {
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_points(10)),
+                |(points, targets)| {
+                    let (index, permutation) = KdTree::new_with_permutation(
+                        points
+                            .iter()
+                            .map(|point| RandomObject(*point))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    for target in targets {
+                        let result1 = points
+                            .iter()
+                            .map(|point| point.distance_2(&target))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                            .unwrap();
+
+                        let result2 = index.nearest_original_index(&target, &permutation).unwrap();
+
+                        assert_eq!(result1, points[result2].distance_2(&target));
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
 }
@@ -0,0 +1,93 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use num_traits::Float;
+
+use crate::{index_of, Distance, KdTree, Object, Point};
+
+/// An owned handle to a single object stored in a shared slice
+///
+/// The handle keeps the whole slice alive via its [`Arc`] so that it does not borrow the tree it was taken from.
+/// It can be cloned cheaply and sent to other threads if the objects are [`Send`] and [`Sync`].
+#[derive(Debug)]
+pub struct ArcRef<O> {
+    objects: Arc<[O]>,
+    index: usize,
+}
+
+impl<O> ArcRef<O> {
+    /// Return the index of the referenced object within the shared slice
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<O> Clone for ArcRef<O> {
+    fn clone(&self) -> Self {
+        Self {
+            objects: self.objects.clone(),
+            index: self.index,
+        }
+    }
+}
+
+impl<O> Deref for ArcRef<O> {
+    type Target = O;
+
+    fn deref(&self) -> &Self::Target {
+        &self.objects[self.index]
+    }
+}
+
+impl<O> KdTree<O, Arc<[O]>>
+where
+    O: Object,
+    O::Point: Distance,
+    <O::Point as Point>::Coord: Float,
+{
+    /// Find the object nearest to the given `target` and return an owned handle to it
+    ///
+    /// In contrast to [`nearest`][Self::nearest], the result does not borrow the tree but shares ownership of its storage.
+    /// Hence the objects stay alive as long as the returned handle does, even if the tree itself is dropped in the meantime.
+    pub fn nearest_owned(&self, target: &O::Point) -> Option<ArcRef<O>> {
+        let object = self.nearest(target)?;
+
+        Some(ArcRef {
+            index: index_of(&self.objects, object),
+            objects: self.objects.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::tests::{random_objects, random_points, RandomObject};
+
+    #[test]
+    fn random_nearest_owned() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10)),
+                |(objects, targets)| {
+                    let index = KdTree::<RandomObject, Arc<[_]>>::new_unchecked(
+                        KdTree::new(objects).into_storage().into(),
+                    );
+
+                    for target in targets {
+                        let result1 = index.nearest(&target).unwrap();
+                        let result2 = index.nearest_owned(&target).unwrap();
+
+                        assert_eq!(result1, &*result2);
+                        assert_eq!(result1, &index[result2.index()]);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
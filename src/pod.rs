@@ -0,0 +1,394 @@
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+use bytemuck::{cast_slice, cast_slice_mut, Pod};
+
+use crate::{KdTree, Object, Point};
+
+const MAGIC: [u8; 6] = *b"SIFKDT";
+const VERSION: u8 = 1;
+
+const LITTLE_ENDIAN: u8 = 1;
+const BIG_ENDIAN: u8 = 2;
+
+const NATIVE_ENDIAN: u8 = if cfg!(target_endian = "little") {
+    LITTLE_ENDIAN
+} else {
+    BIG_ENDIAN
+};
+
+/// The number of bytes read at once so that a corrupt header cannot trigger a large allocation before the data actually arrives
+const CHUNK_SIZE: usize = 64 * 1024;
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object + Pod,
+    S: AsRef<[O]>,
+{
+    /// View the objects of this tree as raw bytes, e.g. to write them to a file which is later memory mapped
+    ///
+    /// The bytes use the native endianness and layout of `O`, so they can only be read back on platforms where these match.
+    ///
+    /// Requires the `bytemuck` feature.
+    pub fn as_bytes(&self) -> &[u8] {
+        cast_slice(self.objects.as_ref())
+    }
+
+    /// Write the objects of this tree to `writer` preceded by a header describing their format
+    ///
+    /// The header consists of magic bytes, a format version, the endianness of the platform, the dimension of the positions, the size of the objects and their number.
+    /// It allows [`deserialize_from`][KdTree::deserialize_from] to reject data written on a platform whose representation of the objects does not match,
+    /// instead of silently producing corrupt objects. The objects themselves are written like [`as_bytes`][Self::as_bytes].
+    ///
+    /// Requires the `bytemuck` feature.
+    pub fn serialize_to<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let objects = self.objects.as_ref();
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION, NATIVE_ENDIAN])?;
+        writer.write_all(&(O::Point::DIM as u64).to_le_bytes())?;
+        writer.write_all(&(size_of::<O>() as u64).to_le_bytes())?;
+        writer.write_all(&(objects.len() as u64).to_le_bytes())?;
+
+        writer.write_all(cast_slice(objects))
+    }
+}
+
+impl<O> KdTree<O>
+where
+    O: Object + Pod,
+{
+    /// Read a tree from `reader` as written by [`serialize_to`][KdTree::serialize_to]
+    ///
+    /// Fails if the header does not match this version of the format or the endianness of this platform, the dimension of the positions or the size of the objects.
+    /// The objects are read incrementally, so a header announcing more objects than the data actually contains fails with an I/O error instead of allocating memory for all of them.
+    /// The objects are not re-sorted, so like for [`new_unchecked`][Self::new_unchecked], the result is only correct if the data was written from a valid tree.
+    ///
+    /// Requires the `bytemuck` feature.
+    pub fn deserialize_from<R>(mut reader: R) -> Result<Self, FormatError>
+    where
+        R: Read,
+    {
+        let mut magic = [0; 6];
+        reader.read_exact(&mut magic)?;
+
+        if magic != MAGIC {
+            return Err(FormatError::InvalidMagic);
+        }
+
+        let mut flags = [0; 2];
+        reader.read_exact(&mut flags)?;
+
+        let [version, endianness] = flags;
+
+        if version != VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+
+        if endianness != NATIVE_ENDIAN {
+            return Err(FormatError::EndiannessMismatch);
+        }
+
+        let mut read_u64 = || -> io::Result<u64> {
+            let mut bytes = [0; 8];
+            reader.read_exact(&mut bytes)?;
+
+            Ok(u64::from_le_bytes(bytes))
+        };
+
+        let dim = read_u64()?;
+
+        if dim != O::Point::DIM as u64 {
+            return Err(FormatError::DimensionMismatch {
+                expected: O::Point::DIM,
+                found: dim,
+            });
+        }
+
+        let size = read_u64()?;
+
+        if size != size_of::<O>() as u64 {
+            return Err(FormatError::SizeMismatch {
+                expected: size_of::<O>(),
+                found: size,
+            });
+        }
+
+        let len = usize::try_from(read_u64()?).map_err(|_| FormatError::TooLarge)?;
+
+        len.checked_mul(size_of::<O>())
+            .filter(|bytes| *bytes <= isize::MAX as usize)
+            .ok_or(FormatError::TooLarge)?;
+
+        let chunk_len = CHUNK_SIZE.checked_div(size_of::<O>()).unwrap_or(len).max(1);
+
+        let mut objects = Vec::new();
+
+        while objects.len() < len {
+            let start = objects.len();
+            let end = len.min(start + chunk_len);
+
+            objects.resize(end, O::zeroed());
+
+            reader.read_exact(cast_slice_mut(&mut objects[start..]))?;
+        }
+
+        Ok(Self::new_unchecked(objects.into_boxed_slice()))
+    }
+}
+
+/// The error returned by [`KdTree::deserialize_from`]
+///
+/// Requires the `bytemuck` feature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FormatError {
+    /// Reading the data failed
+    Io(io::Error),
+    /// The data does not start with the expected magic bytes
+    InvalidMagic,
+    /// The data was written using an unsupported version of the format
+    UnsupportedVersion(u8),
+    /// The data was written on a platform with a different endianness
+    EndiannessMismatch,
+    /// The data was written for positions of a different dimension
+    DimensionMismatch {
+        /// The dimension of the positions on this platform
+        expected: usize,
+        /// The dimension of the positions in the data
+        found: u64,
+    },
+    /// The data was written for objects of a different size
+    SizeMismatch {
+        /// The size of the objects on this platform
+        expected: usize,
+        /// The size of the objects in the data
+        found: u64,
+    },
+    /// The number of objects in the data is not addressable on this platform
+    TooLarge,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(fmt, "failed to read data: {}", err),
+            Self::InvalidMagic => write!(fmt, "invalid magic bytes"),
+            Self::UnsupportedVersion(version) => {
+                write!(fmt, "unsupported format version {}", version)
+            }
+            Self::EndiannessMismatch => write!(fmt, "data was written with different endianness"),
+            Self::DimensionMismatch { expected, found } => write!(
+                fmt,
+                "expected positions of dimension {} but found {}",
+                expected, found
+            ),
+            Self::SizeMismatch { expected, found } => write!(
+                fmt,
+                "expected objects of size {} but found {}",
+                expected, found
+            ),
+            Self::TooLarge => write!(fmt, "number of objects is not addressable"),
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FormatError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl<'a, O> KdTree<O, &'a [O]>
+where
+    O: Object + Pod,
+{
+    /// Interprets the given `bytes` as a tree without copying them, e.g. as produced by [`as_bytes`][KdTree::as_bytes] and read back via a memory map
+    ///
+    /// Like for [`new_unchecked`][Self::new_unchecked], supplying `bytes` which are not actually sorted as a k-d tree is safe but will lead to incorrect results.
+    /// Use [`is_valid`][Self::is_valid] to check untrusted data.
+    ///
+    /// Requires the `bytemuck` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is not aligned to the alignment of `O` or if its length is not a multiple of the size of `O`.
+    /// Memory maps are page-aligned and hence suitably aligned for all usual types if the tree starts at the beginning of the file,
+    /// whereas bytes read into a `Vec<u8>` usually are not.
+    ///
+    /// # Trees larger than memory
+    ///
+    /// Since [`new`][KdTree::new] sorts in place, trees which do not fit into memory can be built within a writable memory map of a file containing the unsorted objects,
+    /// leaving the paging to the operating system. The selection at each level of the tree scans its subtrees sequentially,
+    /// so building touches each object `O(log n)` times like an external sort would. The file can then be mapped read-only and interpreted using this method.
+    ///
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::fs::OpenOptions;
+    ///
+    /// use bytemuck::cast_slice_mut;
+    /// use memmap2::MmapMut;
+    ///
+    /// use sif_kdtree::KdTree;
+    ///
+    /// let file = OpenOptions::new().read(true).write(true).open("points.bin")?;
+    /// let mut map = unsafe { MmapMut::map_mut(&file)? };
+    ///
+    /// KdTree::new(cast_slice_mut::<u8, [f64; 3]>(&mut map));
+    ///
+    /// map.flush()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn from_bytes_unchecked(bytes: &'a [u8]) -> Self {
+        Self {
+            objects: cast_slice(bytes),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::tests::{random_points, random_queries};
+
+    #[test]
+    fn random_bytes_roundtrip() {
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_queries(10)),
+                |(points, queries)| {
+                    let index1 = KdTree::new(points.into_boxed_slice());
+                    let index2 = KdTree::<[f32; 2], _>::from_bytes_unchecked(index1.as_bytes());
+
+                    assert_eq!(&*index1, &*index2);
+
+                    for query in queries {
+                        assert_eq!(
+                            index1.look_up_collect(&query),
+                            index2.look_up_collect(&query)
+                        );
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_serialize_roundtrip() {
+        TestRunner::default()
+            .run(&random_points(100), |points| {
+                let index1 = KdTree::new(points.into_boxed_slice());
+
+                let mut buf = Vec::new();
+                index1.serialize_to(&mut buf).unwrap();
+
+                let index2 = KdTree::<[f32; 2]>::deserialize_from(&buf[..]).unwrap();
+
+                assert_eq!(&*index1, &*index2);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn deserialize_rejects_mismatches() {
+        let index = KdTree::new(vec![[1.0_f32, 2.0], [3.0, 4.0]]);
+
+        let mut buf = Vec::new();
+        index.serialize_to(&mut buf).unwrap();
+
+        let mut bad_magic = buf.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            KdTree::<[f32; 2]>::deserialize_from(&bad_magic[..]),
+            Err(FormatError::InvalidMagic)
+        ));
+
+        let mut bad_version = buf.clone();
+        bad_version[6] = 0;
+        assert!(matches!(
+            KdTree::<[f32; 2]>::deserialize_from(&bad_version[..]),
+            Err(FormatError::UnsupportedVersion(0))
+        ));
+
+        let mut bad_endianness = buf.clone();
+        bad_endianness[7] = if NATIVE_ENDIAN == LITTLE_ENDIAN {
+            BIG_ENDIAN
+        } else {
+            LITTLE_ENDIAN
+        };
+        assert!(matches!(
+            KdTree::<[f32; 2]>::deserialize_from(&bad_endianness[..]),
+            Err(FormatError::EndiannessMismatch)
+        ));
+
+        assert!(matches!(
+            KdTree::<[f32; 3]>::deserialize_from(&buf[..]),
+            Err(FormatError::DimensionMismatch {
+                expected: 3,
+                found: 2
+            })
+        ));
+
+        assert!(matches!(
+            KdTree::<[f64; 2]>::deserialize_from(&buf[..]),
+            Err(FormatError::SizeMismatch {
+                expected: 16,
+                found: 8
+            })
+        ));
+
+        assert!(matches!(
+            KdTree::<[f32; 2]>::deserialize_from(&buf[..buf.len() - 1]),
+            Err(FormatError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn deserialize_rejects_huge_len() {
+        let index = KdTree::new(vec![[1.0_f32, 2.0], [3.0, 4.0]]);
+
+        let mut buf = Vec::new();
+        index.serialize_to(&mut buf).unwrap();
+
+        let mut overflowing_len = buf.clone();
+        overflowing_len[24..32].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+        assert!(matches!(
+            KdTree::<[f32; 2]>::deserialize_from(&overflowing_len[..]),
+            Err(FormatError::TooLarge)
+        ));
+
+        // Announcing a terabyte of objects fails only when the data runs out.
+        if cfg!(target_pointer_width = "64") {
+            let mut truncated_len = buf;
+            truncated_len[24..32].copy_from_slice(&(1_u64 << 40).to_le_bytes());
+            assert!(matches!(
+                KdTree::<[f32; 2]>::deserialize_from(&truncated_len[..]),
+                Err(FormatError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+            ));
+        }
+    }
+}
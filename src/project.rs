@@ -0,0 +1,75 @@
+use crate::{Object, Point};
+
+/// An adapter positioning an object by the projection of its position onto a subset of the axes
+///
+/// This allows indexing and querying objects only along some of their axes, e.g. to ignore temporal or payload-like coordinates.
+/// The projected position is computed once when the adapter is [constructed][project] and stored alongside the object.
+#[derive(Debug, Clone)]
+pub struct ProjectedObject<O, T, const K: usize> {
+    object: O,
+    position: [T; K],
+}
+
+/// Wrap the given `object` so that it is positioned by the `K`-dimensional projection of its position onto the given `axes`
+///
+/// # Example
+///
+/// ```
+/// use sif_kdtree::{project, KdTree, Object};
+///
+/// struct Sample([f64; 3]);
+///
+/// impl Object for Sample {
+///     type Point = [f64; 3];
+///
+///     fn position(&self) -> &Self::Point {
+///         &self.0
+///     }
+/// }
+///
+/// let index = KdTree::new(
+///     vec![
+///         project(Sample([0.0, 0.0, 10.0]), [0, 1]),
+///         project(Sample([1.0, 1.0, 0.0]), [0, 1]),
+///     ]
+///     .into_boxed_slice(),
+/// );
+///
+/// let nearest = index.nearest(&[0.1, 0.1]).unwrap();
+///
+/// assert_eq!(nearest.object().0, [0.0, 0.0, 10.0]);
+/// ```
+pub fn project<O, const K: usize>(
+    object: O,
+    axes: [usize; K],
+) -> ProjectedObject<O, <O::Point as Point>::Coord, K>
+where
+    O: Object,
+{
+    let position = axes.map(|axis| object.position().coord(axis));
+
+    ProjectedObject { object, position }
+}
+
+impl<O, T, const K: usize> ProjectedObject<O, T, K> {
+    /// Access the wrapped object
+    pub fn object(&self) -> &O {
+        &self.object
+    }
+
+    /// Unwrap the wrapped object
+    pub fn into_object(self) -> O {
+        self.object
+    }
+}
+
+impl<O, const K: usize> Object for ProjectedObject<O, <O::Point as Point>::Coord, K>
+where
+    O: Object,
+{
+    type Point = [<O::Point as Point>::Coord; K];
+
+    fn position(&self) -> &Self::Point {
+        &self.position
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{Distance, Point};
+
+/// A point in the `N`-dimensional unit cube whose coordinate values are quantized to 16 bits
+///
+/// Each coordinate value is stored as a `u16` and decoded on demand into `f32` by dividing by `u16::MAX`,
+/// so that objects can be positioned by these points while taking up only half the memory of `[f32; N]`.
+///
+/// Positions in other domains are mapped into the unit cube by [`new_within`][Self::new_within] given the axis-aligned bounding box (AABB) of the domain, e.g. as computed by [`KdTree::bounds`][crate::KdTree::bounds].
+/// All axes share a common scale, the largest side of the AABB, so that the relation between distances is preserved.
+/// The same AABB must be used for all points and should contain the targets of nearest neighbour search as well since coordinate values outside of it are clamped.
+/// Distances within the unit cube are the original distances divided by that scale.
+///
+/// Quantization moves each coordinate value by at most half a step of `1 / u16::MAX` and hence each position by at most `√N / (2 * u16::MAX)`, times the scale for other domains.
+/// Consequently, nearest neighbour search is exact with respect to the quantized positions
+/// but might return an object which is farther from the target than the true nearest one by up to twice that amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuantizedPoint<const N: usize>(pub [u16; N]);
+
+impl<const N: usize> QuantizedPoint<N> {
+    /// Quantize the given `coords` which are clamped to the unit interval
+    pub fn new(coords: [f32; N]) -> Self {
+        Self(coords.map(|coord| (coord.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16))
+    }
+
+    /// Decode all coordinate values
+    pub fn decode(&self) -> [f32; N] {
+        self.0.map(decode)
+    }
+
+    /// Quantize the given `coords` which are clamped to the given `aabb` by mapping it into the unit cube
+    ///
+    /// The AABB is represented like [`Query::aabb`][crate::Query::aabb], i.e. by the corners with first the smallest and then the largest coordinate values.
+    pub fn new_within(coords: [f32; N], aabb: &([f32; N], [f32; N])) -> Self {
+        let scale = scale(aabb);
+
+        let mut unit = [0.0; N];
+
+        for (axis, unit) in unit.iter_mut().enumerate() {
+            let coord = coords[axis].clamp(aabb.0[axis], aabb.1[axis]);
+
+            *unit = (coord - aabb.0[axis]) / scale;
+        }
+
+        Self::new(unit)
+    }
+
+    /// Decode all coordinate values and map them back from the unit cube into the given `aabb`
+    ///
+    /// This inverts [`new_within`][Self::new_within] up to the quantization error if the same `aabb` is given.
+    pub fn decode_within(&self, aabb: &([f32; N], [f32; N])) -> [f32; N] {
+        let scale = scale(aabb);
+
+        let mut coords = self.decode();
+
+        for (axis, coord) in coords.iter_mut().enumerate() {
+            *coord = aabb.0[axis] + *coord * scale;
+        }
+
+        coords
+    }
+}
+
+impl<const N: usize> Point for QuantizedPoint<N> {
+    const DIM: usize = N;
+
+    type Coord = f32;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        decode(self.0[axis])
+    }
+}
+
+/// Euclidean distance of the decoded coordinate values
+impl<const N: usize> Distance for QuantizedPoint<N> {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        self.decode().distance_2(&other.decode())
+    }
+}
+
+fn decode(coord: u16) -> f32 {
+    coord as f32 / u16::MAX as f32
+}
+
+/// Returns the largest side of the given `aabb`, avoiding division by zero if it is degenerate
+fn scale<const N: usize>(aabb: &([f32; N], [f32; N])) -> f32 {
+    let scale = (0..N).fold(0.0_f32, |scale, axis| {
+        scale.max(aabb.1[axis] - aabb.0[axis])
+    });
+
+    if scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    use crate::{tests::random_points, KdTree, Object};
+
+    struct Sample(QuantizedPoint<2>);
+
+    impl Object for Sample {
+        type Point = QuantizedPoint<2>;
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn random_quantized_nearest() {
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_points(10)),
+                |(points, targets)| {
+                    let index = KdTree::new(
+                        points
+                            .iter()
+                            .map(|point| Sample(QuantizedPoint::new(*point)))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    for target in targets {
+                        let result1 = points
+                            .iter()
+                            .map(|point| point.distance_2(&target).sqrt())
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                            .unwrap();
+
+                        let target = QuantizedPoint::new(target);
+
+                        let result2 = index.nearest(&target).unwrap().0.distance_2(&target).sqrt();
+
+                        assert!((result1 - result2).abs() <= 4.0 / u16::MAX as f32);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_quantized_within_nearest() {
+        let random_points = |len| vec((-100.0_f32..=100.0, 0.0_f32..=10.0), len);
+
+        TestRunner::default()
+            .run(
+                &(random_points(100), random_points(10)),
+                |(points, targets)| {
+                    let points = points.into_iter().map(|(x, y)| [x, y]).collect::<Vec<_>>();
+                    let targets = targets.into_iter().map(|(x, y)| [x, y]).collect::<Vec<_>>();
+
+                    // Targets are clamped like points, so the AABB must contain them as well.
+                    let aabb = KdTree::new([&points[..], &targets[..]].concat())
+                        .bounds()
+                        .unwrap();
+                    let scale = scale(&aabb);
+
+                    let index = KdTree::new(
+                        points
+                            .iter()
+                            .map(|point| Sample(QuantizedPoint::new_within(*point, &aabb)))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    for object in index.iter() {
+                        let point = object.0.decode_within(&aabb);
+
+                        assert!(points.iter().any(|other| {
+                            (0..2).all(|axis| {
+                                (point[axis] - other[axis]).abs() <= scale / u16::MAX as f32
+                            })
+                        }));
+                    }
+
+                    for target in targets {
+                        let result1 = points
+                            .iter()
+                            .map(|point| point.distance_2(&target).sqrt())
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap())
+                            .unwrap();
+
+                        let result2 = index
+                            .nearest(&QuantizedPoint::new_within(target, &aabb))
+                            .unwrap()
+                            .0
+                            .decode_within(&aabb)
+                            .distance_2(&target)
+                            .sqrt();
+
+                        assert!((result1 - result2).abs() <= 4.0 * scale / u16::MAX as f32);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
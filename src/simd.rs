@@ -0,0 +1,101 @@
+use wide::f32x4;
+
+use crate::{Distance, Point};
+
+/// A point in `4 * L`-dimensional real space whose coordinate values are packed into SIMD registers
+///
+/// The coordinate value along axis `a` is stored in lane `a % 4` of register `a / 4`, i.e. the axes are packed consecutively.
+/// Unused lanes should be zero so that they do not contribute to distances.
+///
+/// Computing the squared Euclidean distance uses lane-wise multiply-add so that it vectorizes even for high-dimensional points like embeddings.
+///
+/// Requires the `wide` feature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimdPoint<const L: usize>(pub [f32x4; L]);
+
+impl<const L: usize> Point for SimdPoint<L> {
+    const DIM: usize = 4 * L;
+
+    type Coord = f32;
+
+    fn coord(&self, axis: usize) -> Self::Coord {
+        self.0[axis / 4].to_array()[axis % 4]
+    }
+}
+
+/// Euclidean distance computed lane-wise
+impl<const L: usize> Distance for SimdPoint<L> {
+    fn distance_2(&self, other: &Self) -> Self::Coord {
+        let sum = self
+            .0
+            .iter()
+            .zip(&other.0)
+            .fold(f32x4::splat(0.0), |sum, (lhs, rhs)| {
+                let diff = *lhs - *rhs;
+
+                diff.mul_add(diff, sum)
+            });
+
+        sum.to_array().iter().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::{collection::vec, test_runner::TestRunner};
+
+    use crate::{KdTree, Object};
+
+    struct Embedding(SimdPoint<2>);
+
+    impl Object for Embedding {
+        type Point = SimdPoint<2>;
+
+        fn position(&self) -> &Self::Point {
+            &self.0
+        }
+    }
+
+    fn pack(coords: &[f32]) -> SimdPoint<2> {
+        SimdPoint([
+            f32x4::from([coords[0], coords[1], coords[2], coords[3]]),
+            f32x4::from([coords[4], coords[5], coords[6], coords[7]]),
+        ])
+    }
+
+    #[test]
+    fn random_simd_nearest() {
+        TestRunner::default()
+            .run(
+                &(vec(0.0_f32..=1.0, 800), vec(0.0_f32..=1.0, 80)),
+                |(coords, targets)| {
+                    let index = KdTree::new(
+                        coords
+                            .chunks(8)
+                            .map(|coords| Embedding(pack(coords)))
+                            .collect::<Box<[_]>>(),
+                    );
+
+                    for target in targets.chunks(8) {
+                        let target = pack(target);
+
+                        let result1 = index
+                            .iter()
+                            .map(|embedding| embedding.0.distance_2(&target))
+                            .min_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap());
+
+                        let result2 = index
+                            .nearest(&target)
+                            .map(|embedding| embedding.0.distance_2(&target));
+
+                        assert_eq!(result1, result2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
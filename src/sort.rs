@@ -1,9 +1,16 @@
-use std::marker::PhantomData;
+use core::fmt;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::error::Error;
+#[cfg(feature = "std")]
+use std::iter::FromIterator;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "rayon")]
 use rayon::join;
 
-use crate::{KdTree, Object, Point};
+use crate::{separates, KdTree, Object, Point};
 
 impl<O, S> KdTree<O, S>
 where
@@ -20,6 +27,141 @@ where
         }
     }
 
+    /// Construct a new tree by sorting the given `objects`, failing if any of their coordinate values are not comparable
+    ///
+    /// [`new`][Self::new] panics if it encounters coordinate values which are not comparable to themselves, e.g. `NaN`.
+    /// This instead checks all coordinate values before sorting and fails with the first offending object, leaving `objects` unchanged.
+    pub fn try_new(objects: S) -> Result<Self, NonComparableCoord> {
+        for (index, object) in objects.as_ref().iter().enumerate() {
+            let position = object.position();
+
+            for axis in 0..O::Point::DIM {
+                let coord = position.coord(axis);
+
+                if coord.partial_cmp(&coord).is_none() {
+                    return Err(NonComparableCoord { index, axis });
+                }
+            }
+        }
+
+        Ok(Self::new(objects))
+    }
+
+    #[cfg(feature = "std")]
+    /// Construct a new tree by sorting the given `objects`, also returning statistics on the construction
+    ///
+    /// This performs the same construction as [`new`][Self::new] but additionally measures its duration and counts the comparisons of coordinate values,
+    /// e.g. to check that construction cost scales as `O(n log n)` for a given distribution of objects.
+    pub fn new_timed(mut objects: S) -> (Self, BuildStats) {
+        let mut stats = BuildStats::default();
+
+        let start = Instant::now();
+
+        sort_counted(objects.as_mut(), 0, 1, &mut stats);
+
+        stats.duration = start.elapsed();
+
+        let this = Self {
+            objects,
+            _marker: PhantomData,
+        };
+
+        (this, stats)
+    }
+
+    #[cfg(feature = "std")]
+    /// Construct a new tree by sorting the given `objects`, also returning the applied permutation
+    ///
+    /// The object at position `i` of the tree was at position `permutation[i]` of the given `objects`.
+    ///
+    /// Note that determining the permutation requires additional temporary memory and that the permutation itself takes up an additional `usize` per object.
+    pub fn new_with_permutation(mut objects: S) -> (Self, Vec<usize>) {
+        let objects_mut = objects.as_mut();
+
+        let mut indexed = objects_mut
+            .iter()
+            .enumerate()
+            .map(|(index, object)| Indexed(index, object))
+            .collect::<Vec<_>>();
+
+        sort(&mut indexed, 0);
+
+        let permutation = indexed
+            .into_iter()
+            .map(|Indexed(index, _)| index)
+            .collect::<Vec<_>>();
+
+        permute(objects_mut, &permutation);
+
+        let this = Self {
+            objects,
+            _marker: PhantomData,
+        };
+
+        (this, permutation)
+    }
+
+    /// Access the objects of this tree mutably, e.g. to modify their positions before calling [`rebuild`][Self::rebuild]
+    ///
+    /// Modifying the positions is safe, but queries will yield incorrect results until the tree is rebuilt or [repaired][Self::repair].
+    pub fn get_mut(&mut self) -> &mut [O] {
+        self.objects.as_mut()
+    }
+
+    /// Re-sort the objects of this tree in place
+    ///
+    /// This restores the tree after the positions of its objects were modified via [`get_mut`][Self::get_mut] while keeping the existing allocation.
+    pub fn rebuild(&mut self) {
+        sort(self.objects.as_mut(), 0);
+    }
+
+    /// Restore the tree after the positions of some of its objects were modified by re-sorting only the affected subtrees
+    ///
+    /// Each subtree is checked top-down whether its root still separates its left and right children along its axis.
+    /// If not, the whole subtree is re-sorted, otherwise its children are checked in turn. Returns the number of re-sorted subtrees.
+    ///
+    /// Checking the invariant visits each object once per level of the tree, so this is only cheaper than re-sorting the whole tree
+    /// if the modifications are small and do not move objects out of their subtrees near the root.
+    pub fn repair(&mut self) -> usize {
+        repair(self.objects.as_mut(), 0)
+    }
+
+    #[cfg(feature = "std")]
+    /// Restore the order in which the objects were passed to [`new_with_permutation`][Self::new_with_permutation]
+    ///
+    /// The given `permutation` must be the one returned together with this tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `permutation` does not match the number of objects
+    /// or if `permutation` is not actually a permutation, i.e. contains an index which is out of bounds or repeated.
+    pub fn into_original_order(self, permutation: Vec<usize>) -> Vec<O>
+    where
+        S: Into<Vec<O>>,
+    {
+        let mut objects = self.objects.into();
+
+        let len = objects.len();
+
+        assert_eq!(len, permutation.len());
+
+        let mut inverse = vec![usize::MAX; len];
+
+        for (index, original_index) in permutation.into_iter().enumerate() {
+            assert!(
+                original_index < len && inverse[original_index] == usize::MAX,
+                "permutation contains index {} which is out of bounds or repeated",
+                original_index
+            );
+
+            inverse[original_index] = index;
+        }
+
+        permute(&mut objects, &inverse);
+
+        objects
+    }
+
     #[cfg(feature = "rayon")]
     /// Construct a new tree by sorting the given `objects`, in parallel
     ///
@@ -35,9 +177,36 @@ where
             _marker: PhantomData,
         }
     }
+
+    #[cfg(feature = "rayon")]
+    /// Re-sort the objects of this tree in place, in parallel
+    ///
+    /// This restores the tree after the positions of its objects were modified via its backing storage while keeping the existing allocation.
+    ///
+    /// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+    pub fn par_rebuild(&mut self)
+    where
+        O: Send,
+    {
+        par_sort(self.objects.as_mut(), 0);
+    }
+}
+
+#[cfg(feature = "std")]
+/// Collects the objects into a boxed slice and sorts them like [`KdTree::new`]
+impl<O> FromIterator<O> for KdTree<O>
+where
+    O: Object,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = O>,
+    {
+        Self::new(iter.into_iter().collect())
+    }
 }
 
-fn sort<O>(objects: &mut [O], axis: usize)
+pub(crate) fn sort<O>(objects: &mut [O], axis: usize)
 where
     O: Object,
 {
@@ -51,6 +220,65 @@ where
     sort(right, next_axis);
 }
 
+#[cfg(feature = "std")]
+/// Statistics on the construction of a tree as returned by [`KdTree::new_timed`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BuildStats {
+    /// The wall-clock time spent sorting the objects
+    pub duration: Duration,
+    /// The number of nodes, i.e. objects, in the tree
+    pub nodes: usize,
+    /// The number of levels of the tree, i.e. zero for an empty tree and one for a tree containing a single object
+    pub max_depth: usize,
+    /// The number of comparisons of coordinate values performed by the selection of the median at each node
+    pub comparisons: usize,
+}
+
+#[cfg(feature = "std")]
+fn sort_counted<O>(objects: &mut [O], axis: usize, depth: usize, stats: &mut BuildStats)
+where
+    O: Object,
+{
+    if objects.is_empty() {
+        return;
+    }
+
+    stats.nodes += 1;
+    stats.max_depth = stats.max_depth.max(depth);
+
+    if objects.len() == 1 {
+        return;
+    }
+
+    let (left, right, next_axis) = sort_axis_with(objects, axis, || stats.comparisons += 1);
+
+    sort_counted(left, next_axis, depth + 1, stats);
+    sort_counted(right, next_axis, depth + 1, stats);
+}
+
+fn repair<O>(objects: &mut [O], axis: usize) -> usize
+where
+    O: Object,
+{
+    if objects.len() <= 1 {
+        return 0;
+    }
+
+    if !separates(objects, axis) {
+        sort(objects, axis);
+
+        return 1;
+    }
+
+    let mid = objects.len() / 2;
+
+    let (left, right) = objects.split_at_mut(mid);
+
+    let next_axis = (axis + 1) % O::Point::DIM;
+
+    repair(left, next_axis) + repair(&mut right[1..], next_axis)
+}
+
 #[cfg(feature = "rayon")]
 fn par_sort<O>(objects: &mut [O], axis: usize)
 where
@@ -65,13 +293,103 @@ where
     join(|| par_sort(left, next_axis), || par_sort(right, next_axis));
 }
 
-fn sort_axis<O>(objects: &mut [O], axis: usize) -> (&mut [O], &mut [O], usize)
+#[cfg(feature = "std")]
+struct Indexed<'a, O>(usize, &'a O);
+
+#[cfg(feature = "std")]
+impl<O> Object for Indexed<'_, O>
 where
     O: Object,
+{
+    type Point = O::Point;
+
+    fn position(&self) -> &Self::Point {
+        self.1.position()
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn permute<O>(objects: &mut [O], permutation: &[usize]) {
+    let mut done = vec![false; objects.len()];
+
+    for start in 0..objects.len() {
+        if done[start] {
+            continue;
+        }
+
+        done[start] = true;
+
+        let mut pos = start;
+
+        loop {
+            let next = permutation[pos];
+
+            if next == start {
+                break;
+            }
+
+            objects.swap(pos, next);
+            done[next] = true;
+
+            pos = next;
+        }
+    }
+}
+
+/// The error returned by [`KdTree::try_new`] if a coordinate value is not comparable, e.g. `NaN`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonComparableCoord {
+    index: usize,
+    axis: usize,
+}
+
+impl NonComparableCoord {
+    /// Return the index of the first offending object in the given objects
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Return the axis along which the coordinate value of the offending object is not comparable
+    pub fn axis(&self) -> usize {
+        self.axis
+    }
+}
+
+impl fmt::Display for NonComparableCoord {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "coordinate value of object at index {} along axis {} is not comparable",
+            self.index, self.axis
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for NonComparableCoord {}
+
+pub(crate) fn sort_axis<O>(objects: &mut [O], axis: usize) -> (&mut [O], &mut [O], usize)
+where
+    O: Object,
+{
+    sort_axis_with(objects, axis, || ())
+}
+
+/// Works like [`sort_axis`] but calls `on_compare` for each comparison of coordinate values
+fn sort_axis_with<O, F>(
+    objects: &mut [O],
+    axis: usize,
+    mut on_compare: F,
+) -> (&mut [O], &mut [O], usize)
+where
+    O: Object,
+    F: FnMut(),
 {
     let mid = objects.len() / 2;
 
     let (left, _, right) = objects.select_nth_unstable_by(mid, |lhs, rhs| {
+        on_compare();
+
         let lhs = lhs.position().coord(axis);
         let rhs = rhs.position().coord(axis);
 
@@ -82,3 +400,238 @@ where
 
     (left, right, next_axis)
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use std::ops::ControlFlow;
+
+    use proptest::{collection::vec, strategy::Strategy, test_runner::TestRunner};
+
+    #[cfg(feature = "rayon")]
+    use crate::tests::{assert_look_up_matches_brute_force, random_points};
+    use crate::{
+        tests::{random_objects, random_queries},
+        Query,
+    };
+
+    #[test]
+    fn random_into_original_order() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let (index, permutation) = KdTree::new_with_permutation(objects.clone());
+
+                let objects1 = index.into_original_order(permutation);
+
+                assert_eq!(objects1, objects.into_vec());
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "permutation contains index 1 which is out of bounds or repeated")]
+    fn into_original_order_rejects_repeated_index() {
+        let (index, _permutation) = KdTree::new_with_permutation(vec![[0.0], [1.0]]);
+
+        index.into_original_order(vec![1, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "permutation contains index 2 which is out of bounds or repeated")]
+    fn into_original_order_rejects_index_out_of_bounds() {
+        let (index, _permutation) = KdTree::new_with_permutation(vec![[0.0], [1.0]]);
+
+        index.into_original_order(vec![0, 2]);
+    }
+
+    #[test]
+    fn random_from_iter() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index1 = KdTree::new(objects.clone());
+                let index2 = objects.into_vec().into_iter().collect::<KdTree<_>>();
+
+                assert_eq!(index1.objects, index2.objects);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_into_iter() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                let index = KdTree::new(objects);
+                let objects = index.to_vec();
+
+                assert_eq!(index.into_iter().collect::<Vec<_>>(), objects);
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_rebuild() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    vec((0_usize..100, 0.0_f32..=1.0), 5),
+                    random_queries(10),
+                ),
+                |(objects, modifications, queries)| {
+                    let mut index = KdTree::new(objects);
+
+                    for (idx, coord) in modifications {
+                        index.get_mut()[idx].0[0] = coord;
+                    }
+
+                    index.rebuild();
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn random_new_timed() {
+        TestRunner::default()
+            .run(&(0_usize..200).prop_flat_map(random_objects), |objects| {
+                let len = objects.len();
+
+                let index1 = KdTree::new(objects.clone());
+                let (index2, stats) = KdTree::new_timed(objects);
+
+                assert_eq!(index1.objects, index2.objects);
+
+                assert_eq!(stats.nodes, len);
+                assert_eq!(
+                    stats.max_depth,
+                    (usize::BITS - len.leading_zeros()) as usize
+                );
+                assert!(stats.comparisons >= len.saturating_sub(1));
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn random_repair() {
+        TestRunner::default()
+            .run(
+                &(
+                    random_objects(100),
+                    vec((0_usize..100, 0.0_f32..=1.0), 5),
+                    random_queries(10),
+                ),
+                |(objects, changes, queries)| {
+                    let mut index = KdTree::new(objects);
+
+                    assert_eq!(index.repair(), 0);
+
+                    for (object, coord) in changes {
+                        index.objects[object].0[1] = coord;
+                    }
+
+                    index.repair();
+
+                    assert_eq!(index.repair(), 0);
+
+                    for query in queries {
+                        let mut results1 = index
+                            .iter()
+                            .filter(|object| query.test(object.position()))
+                            .collect::<Vec<_>>();
+
+                        let mut results2 = Vec::new();
+                        let _ = index.look_up(&query, |object| {
+                            results2.push(object);
+                            ControlFlow::Continue(())
+                        });
+
+                        results1.sort_unstable();
+                        results2.sort_unstable();
+                        assert_eq!(results1, results2);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn random_par_rebuild() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(100), random_queries(10)),
+                |(objects, points, queries)| {
+                    let mut index = KdTree::par_new(objects);
+
+                    for (object, point) in index.get_mut().iter_mut().zip(points) {
+                        object.0 = point;
+                    }
+
+                    index.par_rebuild();
+
+                    assert!(index.is_valid());
+
+                    for query in queries {
+                        assert_look_up_matches_brute_force(&index, &query, |visitor| {
+                            index.look_up(&query, visitor)
+                        });
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn try_new_reports_nan() {
+        let objects = vec![[0.0, 1.0], [2.0, 3.0], [4.0, f64::NAN], [f64::NAN, 5.0]];
+
+        let err = KdTree::try_new(objects.clone()).unwrap_err();
+
+        assert_eq!(err.index(), 2);
+        assert_eq!(err.axis(), 1);
+        assert_eq!(
+            err.to_string(),
+            "coordinate value of object at index 2 along axis 1 is not comparable"
+        );
+
+        let err = KdTree::try_new(objects[1..].to_vec()).unwrap_err();
+
+        assert_eq!(err.index(), 1);
+
+        let index = KdTree::try_new(objects[..2].to_vec()).unwrap();
+
+        assert_eq!(&*index, &*KdTree::new(objects[..2].to_vec()));
+    }
+}
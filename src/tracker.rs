@@ -0,0 +1,188 @@
+use std::ops::ControlFlow;
+
+use num_traits::Num;
+
+use crate::{index_of, Distance, KdTree, Object, Query, WithinDistance};
+
+/// Tracks the objects within a given distance of a moving center, reporting only which objects entered or exited between updates
+///
+/// The tracked objects are identified by their indices in the tree, i.e. in the slice it dereferences to,
+/// and the tracker must always be updated using the same tree it was constructed with.
+///
+/// Each [update][Self::update] re-tests the previous matches against the new center and
+/// searches only for objects within the new ball which were not already within the old one.
+/// This pays off if the center moves slowly relative to the distance, i.e. if consecutive balls overlap substantially.
+/// If the center moves by twice the distance or more, the balls are disjoint and an update costs as much as a full [look-up][KdTree::look_up] plus re-testing the previous matches.
+#[derive(Debug, Clone)]
+pub struct ProximityTracker<T, const N: usize> {
+    center: [T; N],
+    distance: T,
+    distance_2: T,
+    matches: Vec<usize>,
+}
+
+/// The change of the set of objects tracked by a [`ProximityTracker`] due to an [update][ProximityTracker::update]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProximityDelta {
+    /// The indices of the objects which are now within the distance but were not before, in ascending order
+    pub entered: Vec<usize>,
+    /// The indices of the objects which were within the distance before but are not anymore, in ascending order
+    pub exited: Vec<usize>,
+}
+
+impl<T, const N: usize> ProximityTracker<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    /// Construct a tracker for the objects in `index` within the Euclidean `distance` of the initial `center`
+    pub fn new<O, S>(index: &KdTree<O, S>, center: [T; N], distance: T) -> Self
+    where
+        O: Object<Point = [T; N]>,
+        S: AsRef<[O]>,
+    {
+        let mut matches = Vec::new();
+
+        let _ = index.look_up(&WithinDistance::new(center, distance), |object| {
+            matches.push(index_of(index, object));
+
+            ControlFlow::Continue(())
+        });
+
+        matches.sort_unstable();
+
+        Self {
+            center,
+            distance,
+            distance_2: distance * distance,
+            matches,
+        }
+    }
+
+    /// Return the current center
+    pub fn center(&self) -> &[T; N] {
+        &self.center
+    }
+
+    /// Return the indices of the objects currently within the distance of the center, in ascending order
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+
+    /// Move the center to the given position and return which objects entered or exited
+    pub fn update<O, S>(&mut self, index: &KdTree<O, S>, center: [T; N]) -> ProximityDelta
+    where
+        O: Object<Point = [T; N]>,
+        S: AsRef<[O]>,
+    {
+        let mut delta = ProximityDelta::default();
+
+        let distance_2 = self.distance_2;
+
+        self.matches.retain(|&idx| {
+            let within = center.distance_2(index[idx].position()) <= distance_2;
+
+            if !within {
+                delta.exited.push(idx);
+            }
+
+            within
+        });
+
+        let query = Entering {
+            query: WithinDistance::new(center, self.distance),
+            previous: self.center,
+            distance_2: self.distance_2,
+        };
+
+        let _ = index.look_up(&query, |object| {
+            delta.entered.push(index_of(index, object));
+
+            ControlFlow::Continue(())
+        });
+
+        delta.entered.sort_unstable();
+
+        self.matches.extend_from_slice(&delta.entered);
+        self.matches.sort_unstable();
+
+        self.center = center;
+
+        delta
+    }
+}
+
+/// Matches the objects within the new ball which were not within the previous one
+struct Entering<T, const N: usize> {
+    query: WithinDistance<T, N>,
+    previous: [T; N],
+    distance_2: T,
+}
+
+impl<T, const N: usize> Query<[T; N]> for Entering<T, N>
+where
+    T: Num + Copy + PartialOrd,
+{
+    fn aabb(&self) -> &([T; N], [T; N]) {
+        self.query.aabb()
+    }
+
+    fn test(&self, position: &[T; N]) -> bool {
+        self.query.test(position) && self.previous.distance_2(position) > self.distance_2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::test_runner::TestRunner;
+
+    use crate::tests::{random_objects, random_points};
+
+    #[test]
+    fn random_proximity_tracker() {
+        TestRunner::default()
+            .run(
+                &(random_objects(100), random_points(10), 0.0_f32..=1.0),
+                |(objects, centers, distance)| {
+                    let index = KdTree::new(objects);
+
+                    let within = |center: &[f32; 2]| {
+                        (0..index.len())
+                            .filter(|&idx| index[idx].0.distance_2(center) <= distance * distance)
+                            .collect::<Vec<_>>()
+                    };
+
+                    let mut tracker = ProximityTracker::new(&index, centers[0], distance);
+                    assert_eq!(tracker.matches(), within(&centers[0]));
+
+                    for center in &centers[1..] {
+                        let previous = tracker.matches().to_vec();
+                        let current = within(center);
+
+                        let delta = tracker.update(&index, *center);
+
+                        assert_eq!(tracker.matches(), current);
+                        assert_eq!(tracker.center(), center);
+
+                        let entered = current
+                            .iter()
+                            .filter(|idx| !previous.contains(idx))
+                            .copied()
+                            .collect::<Vec<_>>();
+                        let exited = previous
+                            .iter()
+                            .filter(|idx| !current.contains(idx))
+                            .copied()
+                            .collect::<Vec<_>>();
+
+                        assert_eq!(delta.entered, entered);
+                        assert_eq!(delta.exited, exited);
+                    }
+
+                    Ok(())
+                },
+            )
+            .unwrap();
+    }
+}
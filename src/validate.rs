@@ -0,0 +1,303 @@
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "rayon")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+#[cfg(feature = "serde")]
+use serde::{de::Error as _, Deserialize, Deserializer};
+
+#[cfg(feature = "rayon")]
+use crate::TreeStats;
+use crate::{separates, split, KdTree, Object, Point};
+
+impl<O, S> KdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]>,
+{
+    /// Interprets the given `objects` as a tree after checking that they are actually sorted as a k-d tree
+    ///
+    /// In contrast to [`new_unchecked`][Self::new_unchecked], this performs the same check as [`is_valid`][Self::is_valid]
+    /// and fails with the first node violating the ordering invariant. Hence it can be used to safely load externally produced data without re-sorting it, e.g. from a read-only memory map.
+    pub fn new_checked(objects: S) -> Result<Self, InvalidTree> {
+        if let Some((index, axis)) = find_violation(objects.as_ref(), 0, 0) {
+            return Err(InvalidTree { index, axis });
+        }
+
+        Ok(Self::new_unchecked(objects))
+    }
+
+    /// Check whether the objects of this tree are actually sorted as a k-d tree
+    ///
+    /// Trees constructed via [`new_unchecked`][Self::new_unchecked] or deserialization are not checked and might violate the ordering invariant, e.g. if a memory-mapped file is corrupt.
+    /// This walks the tree like [`look_up`][Self::look_up] and checks that the coordinate values of all objects in the left subtree of each node are not larger
+    /// and the ones in its right subtree are not smaller than the one of the node along its splitting axis.
+    ///
+    /// Each object is checked once per level of the tree, so this takes `O(n log n)` time without allocating memory.
+    pub fn is_valid(&self) -> bool {
+        find_violation(self.objects.as_ref(), 0, 0).is_none()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, O, S> KdTree<O, S>
+where
+    O: Object,
+    S: AsRef<[O]> + Deserialize<'de>,
+{
+    /// Deserialize a tree and verify that its objects are actually sorted as a k-d tree
+    ///
+    /// The [`Deserialize`] implementation of [`KdTree`] trusts the incoming data like [`new_unchecked`][Self::new_unchecked] does.
+    /// This instead verifies the data like [`new_checked`][Self::new_checked] and fails with a custom error if it is not sorted.
+    /// It can be used via `#[serde(deserialize_with = "KdTree::deserialize_verified")]` on fields of type `KdTree`.
+    ///
+    /// Requires the `serde` feature.
+    pub fn deserialize_verified<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let objects = S::deserialize(deserializer)?;
+
+        Self::new_checked(objects).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(feature = "rayon")]
+/// Validate the given `trees` in parallel and summarize the valid ones
+///
+/// For each tree, this returns either its [statistics][KdTree::stats] if it is [valid][KdTree::is_valid]
+/// or the [index][InvalidTree::index] of the first node violating the ordering invariant, in the order of `trees`.
+/// This is intended for checking many trees loaded via [`new_unchecked`][KdTree::new_unchecked] at once, e.g. memory-mapped index files.
+///
+/// Requires the `rayon` feature and dispatches tasks into the current [thread pool][rayon::ThreadPool].
+pub fn validate_many<O, S, I>(trees: I) -> Vec<Result<TreeStats, usize>>
+where
+    O: Object,
+    S: AsRef<[O]>,
+    I: IntoParallelIterator<Item = KdTree<O, S>>,
+{
+    trees
+        .into_par_iter()
+        .map(|tree| match find_violation(tree.objects.as_ref(), 0, 0) {
+            Some((index, _axis)) => Err(index),
+            None => Ok(tree.stats()),
+        })
+        .collect()
+}
+
+/// The error returned by [`KdTree::new_checked`] if the objects are not sorted as a k-d tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTree {
+    index: usize,
+    axis: usize,
+}
+
+impl InvalidTree {
+    /// Return the index of the first node in pre-order which does not separate its subtrees
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Return the splitting axis of the offending node
+    pub fn axis(&self) -> usize {
+        self.axis
+    }
+}
+
+impl fmt::Display for InvalidTree {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            fmt,
+            "node at index {} does not separate its subtrees along axis {}",
+            self.index, self.axis
+        )
+    }
+}
+
+impl Error for InvalidTree {}
+
+/// Returns the index and the splitting axis of the first node in pre-order whose subtrees are not separated by it
+pub(crate) fn find_violation<O>(objects: &[O], axis: usize, offset: usize) -> Option<(usize, usize)>
+where
+    O: Object,
+{
+    if objects.is_empty() {
+        return None;
+    }
+
+    let (left, _, right) = split(objects);
+
+    if !separates(objects, axis) {
+        return Some((offset + left.len(), axis));
+    }
+
+    let next_axis = (axis + 1) % O::Point::DIM;
+
+    find_violation(left, next_axis, offset)
+        .or_else(|| find_violation(right, next_axis, offset + left.len() + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::mem::swap;
+
+    #[cfg(feature = "rayon")]
+    use proptest::collection::vec;
+    use proptest::test_runner::TestRunner;
+
+    use crate::tests::random_objects;
+    #[cfg(feature = "serde")]
+    use crate::tests::random_points;
+
+    #[test]
+    fn random_is_valid() {
+        TestRunner::default()
+            .run(&random_objects(100), |objects| {
+                assert!(KdTree::new(objects.clone()).is_valid());
+
+                let mut objects = KdTree::new(objects).into_storage();
+
+                // Swap the smallest coordinate value along the first axis in the left subtree of the root
+                // with the largest one in its right subtree, so that the root no longer separates them unless they are equal.
+                let mid = objects.len() / 2;
+
+                let (left, right) = objects.split_at_mut(mid);
+
+                let lhs = left
+                    .iter_mut()
+                    .min_by(|lhs, rhs| lhs.0[0].partial_cmp(&rhs.0[0]).unwrap())
+                    .unwrap();
+
+                let rhs = right[1..]
+                    .iter_mut()
+                    .max_by(|lhs, rhs| lhs.0[0].partial_cmp(&rhs.0[0]).unwrap())
+                    .unwrap();
+
+                let unchanged = lhs.0[0] == rhs.0[0];
+
+                swap(&mut lhs.0[0], &mut rhs.0[0]);
+
+                let index = KdTree::new_unchecked(objects);
+
+                assert_eq!(index.is_valid(), unchanged);
+
+                match KdTree::new_checked(index.into_storage()) {
+                    Ok(index) => {
+                        assert!(unchanged);
+                        assert!(index.is_valid());
+                    }
+                    Err(err) => {
+                        assert!(!unchanged);
+                        assert_eq!(err.index(), mid);
+                        assert_eq!(err.axis(), 0);
+                    }
+                }
+
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn new_checked_reports_violation() {
+        let err = KdTree::new_checked(vec![[2.0], [1.0], [0.0]]).unwrap_err();
+
+        assert_eq!(err.index(), 1);
+        assert_eq!(err.axis(), 0);
+        assert_eq!(
+            err.to_string(),
+            "node at index 1 does not separate its subtrees along axis 0"
+        );
+
+        let err =
+            KdTree::new_checked(vec![[0.0, 1.0], [1.0, 0.0], [2.0, 0.0], [3.0, 0.0]]).unwrap_err();
+
+        assert_eq!(err.index(), 1);
+        assert_eq!(err.axis(), 1);
+
+        assert!(KdTree::new_checked(vec![[0.0], [1.0], [2.0]]).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn random_deserialize_verified() {
+        #[derive(Debug, Deserialize)]
+        struct Index {
+            #[serde(deserialize_with = "KdTree::deserialize_verified")]
+            tree: KdTree<[f32; 2]>,
+        }
+
+        TestRunner::default()
+            .run(&random_points(100), |points| {
+                let tree = KdTree::new(points);
+
+                let json = format!(r#"{{"tree":{}}}"#, serde_json::to_string(&tree).unwrap());
+
+                let index = serde_json::from_str::<Index>(&json).unwrap();
+
+                assert_eq!(&*index.tree, &*tree);
+
+                Ok(())
+            })
+            .unwrap();
+
+        let err = serde_json::from_str::<Index>(r#"{"tree":[[2.0,0.0],[1.0,0.0],[0.0,0.0]]}"#)
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("node at index 1 does not separate its subtrees along axis 0"));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn random_validate_many() {
+        TestRunner::default()
+            .run(&vec(random_objects(100), 0..10), |objects| {
+                let trees = objects
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, objects)| {
+                        if idx % 2 == 0 {
+                            KdTree::new(objects.into_vec())
+                        } else {
+                            KdTree::new_unchecked(objects.into_vec())
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let results = trees
+                    .iter()
+                    .map(|tree| match KdTree::new_checked(&tree[..]) {
+                        Ok(tree) => Ok(tree.stats()),
+                        Err(err) => Err(err.index()),
+                    })
+                    .collect::<Vec<_>>();
+
+                assert_eq!(validate_many(trees), results);
+
+                Ok(())
+            })
+            .unwrap();
+
+        let results = validate_many(vec![
+            KdTree::new(vec![[2.0], [1.0], [0.0]]),
+            KdTree::new_unchecked(vec![[2.0], [1.0], [0.0]]),
+        ]);
+
+        assert_eq!(
+            results,
+            [
+                Ok(TreeStats {
+                    len: 3,
+                    height: 2,
+                    min_leaf_depth: 2,
+                    max_leaf_depth: 2,
+                }),
+                Err(1),
+            ]
+        );
+    }
+}
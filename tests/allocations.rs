@@ -0,0 +1,101 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::ops::ControlFlow;
+
+use sif_kdtree::{look_up_slice, nearest_slice, KdTree, Object, WithinDistance};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+struct Sample([f64; 3]);
+
+impl Object for Sample {
+    type Point = [f64; 3];
+
+    fn position(&self) -> &Self::Point {
+        &self.0
+    }
+}
+
+#[test]
+fn new_does_not_allocate() {
+    let mut objects = (0..1000)
+        .map(|idx| {
+            let idx = idx as f64;
+
+            Sample([idx.sin(), idx.cos(), idx.tan()])
+        })
+        .collect::<Vec<_>>();
+
+    for _ in 0..3 {
+        let allocations = ALLOCATIONS.with(Cell::get);
+
+        let index = KdTree::new(objects);
+        objects = index.into_storage();
+
+        assert_eq!(ALLOCATIONS.with(Cell::get), allocations);
+        assert_eq!(objects.len(), 1000);
+    }
+}
+
+#[test]
+fn queries_do_not_allocate() {
+    let mut objects = (0..1000)
+        .map(|idx| {
+            let idx = idx as f64;
+
+            Sample([idx.sin(), idx.cos(), idx.tan()])
+        })
+        .collect::<Vec<_>>();
+
+    KdTree::new(&mut objects[..]);
+
+    let allocations = ALLOCATIONS.with(Cell::get);
+
+    let index = KdTree::new_unchecked(&objects[..]);
+
+    let mut count = 0;
+
+    let _ = index.look_up(&WithinDistance::new([0.0, 0.0, 0.0], 0.5), |_| {
+        count += 1;
+
+        ControlFlow::Continue(())
+    });
+
+    let _ = look_up_slice(&objects, &WithinDistance::new([0.5, 0.5, 0.5], 0.5), |_| {
+        count += 1;
+
+        ControlFlow::Continue(())
+    });
+
+    let nearest1 = index.nearest(&[0.0, 0.0, 0.0]);
+    let nearest2 = nearest_slice(&objects, &[0.0, 0.0, 0.0]);
+
+    assert_eq!(ALLOCATIONS.with(Cell::get), allocations);
+    assert_ne!(count, 0);
+    assert!(nearest1.is_some() && nearest2.is_some());
+}